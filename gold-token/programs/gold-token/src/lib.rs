@@ -1,6 +1,7 @@
 // lib.rs -> Complete Gold Token Program (Anchor 0.31.1)
 
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{
@@ -12,14 +13,22 @@ use anchor_spl::{
         instruction::{
             initialize_permanent_delegate,
         },
+        onchain::invoke_transfer_checked,
         extension::transfer_hook::instruction::initialize,
 
     },
 };
 use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
 
 // Import the gatekeeper program to use its account types and CPI contexts
 use transfer_hook_gatekeeper::program::TransferHookGatekeeper;
+use transfer_hook_gatekeeper::{
+    BlacklistEntry, Lockup, CONTROLLER_AUTHORITY_SEED, REVIEW_ESCROW_SEED,
+};
 
 
 // This is the Program ID of the main gold token program
@@ -32,6 +41,11 @@ pub mod gold_token {
     // ============================================
     // INITIALIZATION & CONFIGURATION
     // ============================================
+
+    /// @dev Creates the mint, this program's config and the gatekeeper's accounts.
+    /// The custodian, auditor and reserve staleness window start unset, so minting stays
+    /// closed until the admin calls `update_custodian`, `update_auditor` and
+    /// `set_reserve_limits`, and the auditor then attests the reserves.
     pub fn initialize(
         ctx: Context<Initialize>,
         _name: String,
@@ -156,13 +170,16 @@ pub mod gold_token {
             system_program: ctx.accounts.system_program.to_account_info(),
         };
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer_hook_gatekeeper::cpi::initialize(cpi_ctx)?;
+        transfer_hook_gatekeeper::cpi::initialize(cpi_ctx, crate::ID)?;
     
         // Initialize the ExtraAccountMetaList for the transfer hook
         let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::InitializeExtraAccountMetaList {
             payer: ctx.accounts.admin.to_account_info(),
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
             extra_account_meta_list: ctx.accounts.extra_account_meta_list.to_account_info(),
             mint: ctx.accounts.mint.to_account_info(),
+            pause_config: ctx.accounts.config.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
         let cpi_ctx = CpiContext::new(ctx.accounts.gatekeeper_program.to_account_info(), cpi_accounts);
@@ -220,6 +237,93 @@ pub mod gold_token {
         Ok(())
     }
 
+    pub fn update_custodian(ctx: Context<UpdateRole>, new_custodian: Pubkey) -> Result<()> {
+        let old_custodian = ctx.accounts.config.custodian;
+        ctx.accounts.config.custodian = new_custodian;
+
+        emit!(RoleUpdated {
+            role: "custodian".to_string(),
+            old_authority: old_custodian,
+            new_authority: new_custodian,
+        });
+
+        Ok(())
+    }
+
+    pub fn update_auditor(ctx: Context<UpdateRole>, new_auditor: Pubkey) -> Result<()> {
+        let old_auditor = ctx.accounts.config.auditor;
+        ctx.accounts.config.auditor = new_auditor;
+
+        emit!(RoleUpdated {
+            role: "auditor".to_string(),
+            old_authority: old_auditor,
+            new_authority: new_auditor,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Grows a config created before the reserve fields to the current layout.
+    pub fn resize_config(ctx: Context<ResizeConfig>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        {
+            let data = config.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == *Config::DISCRIMINATOR,
+                GoldTokenError::InvalidConfig
+            );
+            require_keys_eq!(
+                Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+                ctx.accounts.admin.key(),
+                GoldTokenError::Unauthorized
+            );
+            if data.len() >= Config::LEN {
+                return Ok(());
+            }
+        }
+
+        let shortfall = Rent::get()?
+            .minimum_balance(Config::LEN)
+            .saturating_sub(config.lamports());
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: config.to_account_info(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        // New fields read as zero: no auditor, no cap, no attestation is fresh enough, and
+        // the next attestation comes after any shortfall already flagged
+        config.resize(Config::LEN)?;
+
+        Ok(())
+    }
+
+    /// @dev Sets the hard supply cap (0 for none) and how long a reserve attestation stays usable.
+    pub fn set_reserve_limits(
+        ctx: Context<UpdateRole>,
+        supply_cap: u64,
+        reserve_staleness_window: i64,
+    ) -> Result<()> {
+        require!(reserve_staleness_window > 0, GoldTokenError::InvalidStalenessWindow);
+        let config = &mut ctx.accounts.config;
+        config.supply_cap = supply_cap;
+        config.reserve_staleness_window = reserve_staleness_window;
+
+        emit!(ReserveLimitsUpdated {
+            supply_cap,
+            reserve_staleness_window,
+            authority: *ctx.accounts.admin.key,
+        });
+
+        Ok(())
+    }
+
     pub fn toggle_pause(ctx: Context<TogglePause>) -> Result<()> {
         let config = &mut ctx.accounts.config;
         config.is_paused = !config.is_paused;
@@ -232,6 +336,34 @@ pub mod gold_token {
         Ok(())
     }
 
+    /// @dev Releases a wallet's settlement lockup before its unlock time.
+    pub fn release_lockup(ctx: Context<ReleaseLockup>) -> Result<()> {
+        let lockup = Lockup::try_deserialize(&mut &ctx.accounts.lockup.try_borrow_data()?[..])?;
+
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::ReleaseLockup {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            wallet: ctx.accounts.wallet.to_account_info(),
+            lockup: ctx.accounts.lockup.to_account_info(),
+            rent_receiver: ctx.accounts.admin.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::release_lockup(cpi_ctx)?;
+
+        emit!(LockupReleased {
+            wallet: *ctx.accounts.wallet.key,
+            locked_amount: lockup.locked_amount,
+            unlock_at: lockup.unlock_at,
+            authority: *ctx.accounts.admin.key,
+        });
+
+        Ok(())
+    }
+
     // ============================================
     // FEE CONTROLLER FUNCTIONS
     // ============================================
@@ -325,13 +457,46 @@ pub mod gold_token {
     // SUPPLY CONTROLLER FUNCTIONS
     // ============================================
 
-    /// @dev Mints new tokens and creates associated token account if needed.
-    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64, recipient: Pubkey) -> Result<()> {
+    /// @dev Mints exactly a bar deposit's fine weight to its recipient, creating the token
+    /// account if needed. The deposit is consumed so it can never back a second mint.
+    pub fn mint_against_deposit(ctx: Context<MintAgainstDeposit>) -> Result<()> {
+        // One token is one gram of fine gold
+        let amount = 10u128
+            .checked_pow(ctx.accounts.mint.decimals as u32)
+            .and_then(|unit| (ctx.accounts.bar_deposit.fine_weight_mg as u128).checked_mul(unit))
+            .map(|base_units| base_units / MG_PER_GRAM as u128)
+            .and_then(|base_units| u64::try_from(base_units).ok())
+            .ok_or(GoldTokenError::DepositAmountOverflow)?;
         require!(amount > 0, GoldTokenError::InvalidAmount);
         require!(!ctx.accounts.config.is_paused, GoldTokenError::ContractPaused);
+        require!(
+            ctx.accounts.config.reserve_shortfall_at == 0,
+            GoldTokenError::ReserveShortfallActive
+        );
+
+        // New supply must stay under the hard cap and the latest audited reserves
+        let config = &ctx.accounts.config;
+        let new_supply = ctx.accounts.mint.supply
+            .checked_add(amount)
+            .ok_or(GoldTokenError::SupplyCapExceeded)?;
+        require!(
+            config.supply_cap == 0 || new_supply <= config.supply_cap,
+            GoldTokenError::SupplyCapExceeded
+        );
+        let attestation = &ctx.accounts.reserve_attestation;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now.saturating_sub(attestation.attested_at) <= config.reserve_staleness_window,
+            GoldTokenError::StaleReserveAttestation
+        );
+        require!(
+            new_supply <= attestation.backed_supply(ctx.accounts.mint.decimals),
+            GoldTokenError::InsufficientReserves
+        );
         
         let seeds = &["mint_authority".as_bytes(), &[ctx.bumps.mint_authority_pda]];
         let signer = &[&seeds[..]];
+        let new_holder = ctx.accounts.recipient_token_account.amount == 0;
         
         mint_to(
             CpiContext::new_with_signer(
@@ -346,11 +511,347 @@ pub mod gold_token {
             amount,
         )?;
 
+        // Hold the new tokens until the settlement lockup expires
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::LockTokens {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            payer: ctx.accounts.supply_controller.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            wallet: ctx.accounts.recipient.to_account_info(),
+            lockup: ctx.accounts.lockup.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::lock_tokens(cpi_ctx, amount)?;
+
+        // Mints skip the transfer hook, so count a wallet that held nothing before
+        if new_holder {
+            let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+            let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::UpdateHolderCount {
+                config: ctx.accounts.gatekeeper_config.to_account_info(),
+                controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+            transfer_hook_gatekeeper::cpi::add_holder(cpi_ctx)?;
+        }
+
+        let deposit = &mut ctx.accounts.bar_deposit;
+        deposit.consumed = true;
+
         emit!(TokensMinted {
             mint: ctx.accounts.mint.key(),
             to: ctx.accounts.recipient_token_account.key(),
             amount,
             authority: *ctx.accounts.supply_controller.key,
+            recipient: deposit.recipient,
+        });
+        emit!(DepositMinted {
+            bar_deposit: deposit.key(),
+            gold_bar: deposit.gold_bar,
+            serial: deposit.serial.clone(),
+            fine_weight_mg: deposit.fine_weight_mg,
+            amount,
+            recipient: deposit.recipient,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // PROOF OF RESERVE
+    // ============================================
+
+    /// @dev Records the auditor's latest count of the gold backing the token.
+    pub fn attest_reserves(
+        ctx: Context<AttestReserves>,
+        vault_grams: u64,
+        report_hash: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.reserve_attestation.record(
+            &mut ctx.accounts.config,
+            &ctx.accounts.mint,
+            vault_grams,
+            report_hash,
+            Clock::get()?.unix_timestamp,
+            *ctx.accounts.auditor.key,
+        )
+    }
+
+    /// @dev Records a reserve report signed off-chain by a registered auditor key.
+    /// The preceding instruction must be an Ed25519 verification of exactly that report.
+    pub fn submit_signed_attestation(
+        ctx: Context<SubmitSignedAttestation>,
+        report: ReserveReport,
+    ) -> Result<()> {
+        require_keys_eq!(
+            report.mint,
+            ctx.accounts.config.mint,
+            GoldTokenError::InvalidReserveReport
+        );
+        // Reports must move forward in time, so a relayed report can't be replayed
+        require!(
+            report.reported_at > ctx.accounts.reserve_attestation.attested_at
+                && report.reported_at <= Clock::get()?.unix_timestamp,
+            GoldTokenError::InvalidReserveReport
+        );
+
+        let instructions = &ctx.accounts.instructions_sysvar;
+        let current_index = load_current_index_checked(instructions)?;
+        require!(current_index > 0, GoldTokenError::InvalidEd25519Instruction);
+        let ed25519_ix = load_instruction_at_checked(current_index as usize - 1, instructions)?;
+        require_keys_eq!(
+            ed25519_ix.program_id,
+            ed25519_program::ID,
+            GoldTokenError::InvalidEd25519Instruction
+        );
+
+        // One signature whose key, signature and message all live in the Ed25519 instruction itself
+        let data = &ed25519_ix.data;
+        require!(data.len() >= 16 && data[0] == 1, GoldTokenError::InvalidEd25519Instruction);
+        let offset = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]) as usize;
+        require!(
+            offset(1) == u16::MAX as usize
+                && offset(3) == u16::MAX as usize
+                && offset(6) == u16::MAX as usize,
+            GoldTokenError::InvalidEd25519Instruction
+        );
+        let (public_key_offset, message_offset, message_size) = (offset(2), offset(4), offset(5));
+        let signer = data
+            .get(public_key_offset..public_key_offset + 32)
+            .ok_or(GoldTokenError::InvalidEd25519Instruction)?;
+        let message = data
+            .get(message_offset..message_offset + message_size)
+            .ok_or(GoldTokenError::InvalidEd25519Instruction)?;
+        require!(
+            message == report.try_to_vec()?.as_slice(),
+            GoldTokenError::InvalidEd25519Instruction
+        );
+
+        let signer = Pubkey::new_from_array(signer.try_into().unwrap());
+        require!(
+            ctx.accounts.auditor_registry.auditors.contains(&signer),
+            GoldTokenError::UnknownAuditorKey
+        );
+
+        ctx.accounts.reserve_attestation.record(
+            &mut ctx.accounts.config,
+            &ctx.accounts.mint,
+            report.vault_grams,
+            report.report_hash,
+            report.reported_at,
+            signer,
+        )
+    }
+
+    /// @dev Registers a key whose signed reserve reports anyone may relay.
+    pub fn add_auditor_key(ctx: Context<AddAuditorKey>, auditor_key: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.auditor_registry;
+        if !registry.auditors.contains(&auditor_key) {
+            require!(
+                registry.auditors.len() < MAX_AUDITOR_KEYS,
+                GoldTokenError::AuditorRegistryFull
+            );
+            registry.auditors.push(auditor_key);
+        }
+
+        emit!(AuditorKeyUpdated {
+            auditor_key,
+            registered: true,
+            authority: *ctx.accounts.admin.key,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove_auditor_key(ctx: Context<RemoveAuditorKey>, auditor_key: Pubkey) -> Result<()> {
+        ctx.accounts.auditor_registry.auditors.retain(|key| *key != auditor_key);
+
+        emit!(AuditorKeyUpdated {
+            auditor_key,
+            registered: false,
+            authority: *ctx.accounts.admin.key,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Lifts the shortfall restriction once an attestation recorded after it covers the supply.
+    /// Attestations are ordered by count rather than time, so one in the same second still counts.
+    pub fn acknowledge_reserve_recovery(ctx: Context<AcknowledgeReserveRecovery>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let attestation = &ctx.accounts.reserve_attestation;
+        require!(config.reserve_shortfall_at != 0, GoldTokenError::NoReserveShortfall);
+        require!(
+            config.reserve_attestation_count > config.reserve_shortfall_sequence
+                && ctx.accounts.mint.supply <= attestation.backed_supply(ctx.accounts.mint.decimals),
+            GoldTokenError::InsufficientReserves
+        );
+        config.reserve_shortfall_at = 0;
+
+        emit!(ReserveShortfallCleared {
+            vault_grams: attestation.vault_grams,
+            attested_at: attestation.attested_at,
+            authority: *ctx.accounts.admin.key,
+        });
+
+        Ok(())
+    }
+
+    // ============================================
+    // GOLD BAR REGISTRY
+    // ============================================
+
+    /// @dev Opens a vault whose in-vault bars are tallied for reconciliation against supply.
+    pub fn create_vault(ctx: Context<CreateVault>, vault_id: u64, location: String) -> Result<()> {
+        require!(
+            !location.is_empty() && location.len() <= MAX_VAULT_LOCATION_LEN,
+            GoldTokenError::InvalidVaultDetails
+        );
+        let vault = &mut ctx.accounts.vault;
+        vault.vault_id = vault_id;
+        vault.location = location.clone();
+        vault.fine_weight_mg = 0;
+        vault.bar_count = 0;
+
+        emit!(VaultCreated {
+            vault: vault.key(),
+            vault_id,
+            location,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Registers a bar as held in `vault` and adds its fine weight to the vault total.
+    pub fn register_gold_bar(
+        ctx: Context<RegisterGoldBar>,
+        refiner: String,
+        serial: String,
+        gross_weight_mg: u64,
+        fineness: u16,
+    ) -> Result<()> {
+        require!(
+            !refiner.is_empty() && refiner.len() <= MAX_BAR_FIELD_LEN,
+            GoldTokenError::InvalidBarDetails
+        );
+        require!(
+            !serial.is_empty() && serial.len() <= MAX_BAR_FIELD_LEN,
+            GoldTokenError::InvalidBarDetails
+        );
+        require!(
+            gross_weight_mg > 0 && fineness > 0 && fineness <= FINENESS_SCALE,
+            GoldTokenError::InvalidBarDetails
+        );
+        // Fine weight is the pure gold content: gross weight scaled by fineness in parts per 10,000
+        let fine_weight_mg =
+            (gross_weight_mg as u128 * fineness as u128 / FINENESS_SCALE as u128) as u64;
+
+        let vault = &mut ctx.accounts.vault;
+        vault.fine_weight_mg = vault.fine_weight_mg
+            .checked_add(fine_weight_mg)
+            .ok_or(GoldTokenError::CounterOverflow)?;
+        vault.bar_count = vault.bar_count
+            .checked_add(1)
+            .ok_or(GoldTokenError::CounterOverflow)?;
+
+        let bar = &mut ctx.accounts.gold_bar;
+        bar.refiner = refiner.clone();
+        bar.serial = serial.clone();
+        bar.gross_weight_mg = gross_weight_mg;
+        bar.fineness = fineness;
+        bar.fine_weight_mg = fine_weight_mg;
+        bar.vault = vault.key();
+        bar.status = BarStatus::InVault;
+        bar.registered_at = Clock::get()?.unix_timestamp;
+
+        emit!(GoldBarRegistered {
+            gold_bar: bar.key(),
+            refiner,
+            serial,
+            fine_weight_mg,
+            vault: bar.vault,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Changes a bar's status, moving its fine weight in or out of the vault total.
+    /// Redeemed bars are final.
+    pub fn update_gold_bar_status(
+        ctx: Context<UpdateGoldBarStatus>,
+        new_status: BarStatus,
+    ) -> Result<()> {
+        let bar = &mut ctx.accounts.gold_bar;
+        let old_status = bar.status.clone();
+        require!(
+            old_status != new_status && old_status != BarStatus::Redeemed,
+            GoldTokenError::InvalidBarStatus
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        if old_status == BarStatus::InVault {
+            vault.fine_weight_mg = vault.fine_weight_mg
+                .checked_sub(bar.fine_weight_mg)
+                .ok_or(GoldTokenError::CounterOverflow)?;
+            vault.bar_count = vault.bar_count
+                .checked_sub(1)
+                .ok_or(GoldTokenError::CounterOverflow)?;
+        } else if new_status == BarStatus::InVault {
+            vault.fine_weight_mg = vault.fine_weight_mg
+                .checked_add(bar.fine_weight_mg)
+                .ok_or(GoldTokenError::CounterOverflow)?;
+            vault.bar_count = vault.bar_count
+                .checked_add(1)
+                .ok_or(GoldTokenError::CounterOverflow)?;
+        }
+        bar.status = new_status.clone();
+
+        emit!(GoldBarStatusChanged {
+            gold_bar: bar.key(),
+            old_status,
+            new_status,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Reassigns an in-transit bar to its destination vault.
+    /// Marking it `InVault` afterwards adds it to the new vault's total.
+    pub fn move_gold_bar(ctx: Context<MoveGoldBar>) -> Result<()> {
+        let bar = &mut ctx.accounts.gold_bar;
+        require!(bar.status == BarStatus::InTransit, GoldTokenError::InvalidBarStatus);
+        let from_vault = bar.vault;
+        bar.vault = ctx.accounts.to_vault.key();
+
+        emit!(GoldBarMoved {
+            gold_bar: bar.key(),
+            from_vault,
+            to_vault: bar.vault,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Records an incoming bar as a deposit whose fine weight is owed to `recipient`.
+    pub fn register_bar_deposit(ctx: Context<RegisterBarDeposit>, recipient: Pubkey) -> Result<()> {
+        let bar = &ctx.accounts.gold_bar;
+        let deposit = &mut ctx.accounts.bar_deposit;
+        deposit.gold_bar = bar.key();
+        deposit.serial = bar.serial.clone();
+        deposit.fine_weight_mg = bar.fine_weight_mg;
+        deposit.recipient = recipient;
+        deposit.registered_at = Clock::get()?.unix_timestamp;
+        deposit.consumed = false;
+
+        emit!(BarDepositRegistered {
+            bar_deposit: deposit.key(),
+            gold_bar: deposit.gold_bar,
+            serial: deposit.serial.clone(),
+            fine_weight_mg: deposit.fine_weight_mg,
             recipient,
         });
 
@@ -426,6 +927,7 @@ pub mod gold_token {
             &[request.redemption_pda_bump]
         ];
         let signer = &[&seeds[..]];
+        let emptied = ctx.accounts.user_token_account.amount == request.amount;
         
         burn(
             CpiContext::new_with_signer(
@@ -439,6 +941,20 @@ pub mod gold_token {
             ),
             request.amount,
         )?;
+
+        // Burns skip the transfer hook, so stop counting a wallet left empty
+        if emptied {
+            let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+            let controller_signer = &[&controller_seeds[..]];
+            let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+            let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::UpdateHolderCount {
+                config: ctx.accounts.gatekeeper_config.to_account_info(),
+                controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+            transfer_hook_gatekeeper::cpi::remove_holder(cpi_ctx)?;
+        }
         
         request.status = RedemptionStatus::Fulfilled;
         request.completed_at = Clock::get()?.unix_timestamp;
@@ -495,27 +1011,161 @@ pub mod gold_token {
         Ok(())
     }
 
+    // ============================================
+    // COMPLIANCE REVIEW ESCROW
+    // ============================================
+
+    /// @dev Moves a large transfer into escrow until asset protection reviews it.
+    /// Remaining accounts are the transfer hook accounts for the deposit.
+    pub fn transfer_via_review<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferViaReview<'info>>,
+        review_id: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, GoldTokenError::InvalidAmount);
+        require!(!ctx.accounts.config.is_paused, GoldTokenError::ContractPaused);
+        require!(
+            ctx.accounts.sender_token_account.amount >= amount,
+            GoldTokenError::InsufficientBalance
+        );
+
+        // Record what actually reached escrow, net of any transfer fee
+        let escrow_before = ctx.accounts.escrow_token_account.amount;
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.sender_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            ctx.accounts.mint.decimals,
+            &[],
+        )?;
+        ctx.accounts.escrow_token_account.reload()?;
+        let escrowed = ctx.accounts.escrow_token_account.amount.saturating_sub(escrow_before);
+
+        let review = &mut ctx.accounts.review_transfer;
+        review.review_id = review_id;
+        review.sender = *ctx.accounts.sender.key;
+        review.destination = *ctx.accounts.destination.key;
+        review.amount = escrowed;
+        review.requested_at = Clock::get()?.unix_timestamp;
+
+        emit!(ReviewTransferRequested {
+            review_id,
+            sender: review.sender,
+            destination: review.destination,
+            amount: escrowed,
+            timestamp: review.requested_at,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Releases an escrowed transfer to its destination.
+    /// Remaining accounts are the transfer hook accounts for the release.
+    pub fn approve_review_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveReviewTransfer<'info>>,
+    ) -> Result<()> {
+        let review = &ctx.accounts.review_transfer;
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.owner,
+            review.destination,
+            GoldTokenError::InvalidReviewRecipient
+        );
+        let escrow_seeds = &[REVIEW_ESCROW_SEED, &[ctx.bumps.escrow_authority]];
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.recipient_token_account.to_account_info(),
+            ctx.accounts.escrow_authority.to_account_info(),
+            ctx.remaining_accounts,
+            review.amount,
+            ctx.accounts.mint.decimals,
+            &[&escrow_seeds[..]],
+        )?;
+
+        emit!(ReviewTransferApproved {
+            review_id: review.review_id,
+            sender: review.sender,
+            destination: review.destination,
+            amount: review.amount,
+            authority: *ctx.accounts.asset_protection.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Returns an escrowed transfer to its sender.
+    /// Remaining accounts are the transfer hook accounts for the return.
+    pub fn reject_review_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolveReviewTransfer<'info>>,
+    ) -> Result<()> {
+        let review = &ctx.accounts.review_transfer;
+        require_keys_eq!(
+            ctx.accounts.recipient_token_account.owner,
+            review.sender,
+            GoldTokenError::InvalidReviewRecipient
+        );
+        let escrow_seeds = &[REVIEW_ESCROW_SEED, &[ctx.bumps.escrow_authority]];
+        invoke_transfer_checked(
+            ctx.accounts.token_program.key,
+            ctx.accounts.escrow_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.recipient_token_account.to_account_info(),
+            ctx.accounts.escrow_authority.to_account_info(),
+            ctx.remaining_accounts,
+            review.amount,
+            ctx.accounts.mint.decimals,
+            &[&escrow_seeds[..]],
+        )?;
+
+        emit!(ReviewTransferRejected {
+            review_id: review.review_id,
+            sender: review.sender,
+            destination: review.destination,
+            amount: review.amount,
+            authority: *ctx.accounts.asset_protection.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
     // ============================================
     // ASSET PROTECTION: BLACKLIST & WIPE
     // ============================================
 
     /// @dev Adds an address to the transfer blacklist by calling the gatekeeper program.
-    pub fn add_to_blacklist(ctx: Context<UpdateBlacklist>) -> Result<()> {
+    pub fn add_to_blacklist(
+        ctx: Context<UpdateBlacklist>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
         let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
         let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::AddToBlacklist {
             config: ctx.accounts.gatekeeper_config.to_account_info(),
             authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
             target_address: ctx.accounts.target_address.to_account_info(),
             blacklist_entry: ctx.accounts.blacklist_entry.to_account_info(),
             mint: ctx.accounts.mint.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        transfer_hook_gatekeeper::cpi::add_to_blacklist(cpi_ctx)?;
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::add_to_blacklist(cpi_ctx, reason_code, case_reference)?;
 
         emit!(AddressBlacklisted {
             address: *ctx.accounts.target_address.key,
             authority: *ctx.accounts.asset_protection.key,
+            reason_code,
+            case_reference,
+            added_at: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
@@ -523,80 +1173,315 @@ pub mod gold_token {
 
     /// @dev Removes an address from the transfer blacklist.
     pub fn remove_from_blacklist(ctx: Context<RemoveBlacklist>) -> Result<()> {
+        // Read the case details before the gatekeeper closes the entry
+        let entry = BlacklistEntry::try_deserialize(
+            &mut &ctx.accounts.blacklist_entry.try_borrow_data()?[..],
+        )?;
+
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
         let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
         let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::RemoveFromBlacklist {
             config: ctx.accounts.gatekeeper_config.to_account_info(),
             authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
             target_address: ctx.accounts.target_address.to_account_info(),
             blacklist_entry: ctx.accounts.blacklist_entry.to_account_info(),
             mint: ctx.accounts.mint.to_account_info(),
         };
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
         transfer_hook_gatekeeper::cpi::remove_from_blacklist(cpi_ctx)?;
 
         emit!(AddressUnblacklisted {
             address: *ctx.accounts.target_address.key,
             authority: *ctx.accounts.asset_protection.key,
+            reason_code: entry.reason_code,
+            case_reference: entry.case_reference,
+            added_by: entry.added_by,
+            added_at: entry.added_at,
         });
 
         Ok(())
     }
 
-    /// @dev Wipes tokens from a blacklisted address using the Permanent Delegate power.
-    pub fn wipe_blacklisted_address(ctx: Context<WipeAddress>, amount: u64) -> Result<()> {
-        require!(amount > 0, GoldTokenError::InvalidAmount);
-        require!(
-            !ctx.accounts.blacklist_entry.data_is_empty(),
-            GoldTokenError::AddressNotBlacklisted
-        );
+    /// @dev Adds every `[target, entry]` pair in the remaining accounts to the blacklist.
+    pub fn add_to_blacklist_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateBlacklistBatch<'info>>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
         require!(
-            ctx.accounts.target_token_account.amount >= amount,
-            GoldTokenError::InsufficientBalance
+            !ctx.remaining_accounts.is_empty() && pairs.remainder().is_empty(),
+            GoldTokenError::InvalidBatch
         );
-        
-        burn(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Burn {
-                    mint: ctx.accounts.mint.to_account_info(),
-                    from: ctx.accounts.target_token_account.to_account_info(),
-                    authority: ctx.accounts.asset_protection.to_account_info(),
-                },
-            ),
-            amount,
-        )?;
+        let addresses = pairs.map(|pair| pair[0].key()).collect();
 
-        emit!(TokensWiped {
-            target_user: *ctx.accounts.target_user.key,
-            amount,
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::AddToBlacklistBatch {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_hook_gatekeeper::cpi::add_to_blacklist_batch(cpi_ctx, reason_code, case_reference)?;
+
+        emit!(AddressesBlacklisted {
+            addresses,
             authority: *ctx.accounts.asset_protection.key,
+            reason_code,
+            case_reference,
+            added_at: Clock::get()?.unix_timestamp,
         });
 
         Ok(())
     }
-}
 
+    /// @dev Removes every `[target, entry]` pair in the remaining accounts from the blacklist.
+    pub fn remove_from_blacklist_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateBlacklistBatch<'info>>,
+    ) -> Result<()> {
+        // Read the case details before the gatekeeper closes the entries
+        let mut entries = Vec::with_capacity(ctx.remaining_accounts.len() / 2);
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let [target, entry] = pair else {
+                return err!(GoldTokenError::InvalidBatch);
+            };
+            let entry = BlacklistEntry::try_deserialize(&mut &entry.try_borrow_data()?[..])?;
+            entries.push(BlacklistCase {
+                address: target.key(),
+                reason_code: entry.reason_code,
+                case_reference: entry.case_reference,
+                added_by: entry.added_by,
+                added_at: entry.added_at,
+            });
+        }
+
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::RemoveFromBlacklistBatch {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer)
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        transfer_hook_gatekeeper::cpi::remove_from_blacklist_batch(cpi_ctx)?;
 
-#[derive(Accounts)]
-#[instruction(name: String, symbol: String, uri: String, transfer_fee_basis_points: u16, maximum_fee: u64)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    /// CHECK: Authority account, constrained at mint creation.
-    pub supply_controller: AccountInfo<'info>,
-    /// Asset protection authority - must be signer for gatekeeper initialization
-    pub asset_protection: Signer<'info>,
-    /// CHECK: Authority account, constrained at mint creation.
-    pub fee_controller: AccountInfo<'info>,
-    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+        emit!(AddressesUnblacklisted {
+            entries,
+            authority: *ctx.accounts.asset_protection.key,
+        });
 
-    #[account(
-        init, 
-        payer = admin, 
-        space = 8 + 32*6 + 8 + 1,
-        seeds = [b"config"], 
-        bump
-    )]
+        Ok(())
+    }
+
+    /// @dev Moves a legacy global blacklist entry under this mint.
+    /// The transfer hook only reads per-mint entries, so an address not yet migrated
+    /// could transfer freely. Pause before the first migration and unpause only once
+    /// every legacy entry has been migrated.
+    pub fn migrate_blacklist_entry(
+        ctx: Context<MigrateBlacklist>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        require!(ctx.accounts.config.is_paused, GoldTokenError::MigrationRequiresPause);
+
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::MigrateBlacklistEntry {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            target_address: ctx.accounts.target_address.to_account_info(),
+            legacy_blacklist_entry: ctx.accounts.legacy_blacklist_entry.to_account_info(),
+            blacklist_entry: ctx.accounts.blacklist_entry.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::migrate_blacklist_entry(cpi_ctx, reason_code, case_reference)?;
+
+        emit!(AddressBlacklisted {
+            address: *ctx.accounts.target_address.key,
+            authority: *ctx.accounts.asset_protection.key,
+            reason_code,
+            case_reference,
+            added_at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// @dev Closes a legacy global blacklist entry after it was migrated under this mint.
+    /// The entry is shared by every mint; close it only once all of them have migrated.
+    pub fn close_legacy_blacklist_entry(ctx: Context<CloseLegacyBlacklist>) -> Result<()> {
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::CloseLegacyBlacklistEntry {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            target_address: ctx.accounts.target_address.to_account_info(),
+            legacy_blacklist_entry: ctx.accounts.legacy_blacklist_entry.to_account_info(),
+            blacklist_entry: ctx.accounts.blacklist_entry.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::close_legacy_blacklist_entry(cpi_ctx)?;
+
+        Ok(())
+    }
+
+    /// @dev Fills in case details on a blacklist entry created before they were recorded.
+    pub fn resize_blacklist_entry(
+        ctx: Context<UpdateBlacklist>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::ResizeBlacklistEntry {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            target_address: ctx.accounts.target_address.to_account_info(),
+            blacklist_entry: ctx.accounts.blacklist_entry.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::resize_blacklist_entry(cpi_ctx, reason_code, case_reference)?;
+
+        Ok(())
+    }
+
+    /// @dev Rebuilds the transfer hook's ExtraAccountMetaList after the accounts it needs change.
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::UpdateExtraAccountMetaList {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            extra_account_meta_list: ctx.accounts.extra_account_meta_list.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            pause_config: ctx.accounts.config.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.gatekeeper_program.to_account_info(), cpi_accounts);
+        transfer_hook_gatekeeper::cpi::update_extra_account_meta_list(cpi_ctx)?;
+
+        Ok(())
+    }
+
+    /// @dev Hands gatekeeper co-signing to a new controller program. The admin signs
+    /// alongside the gatekeeper authority since the new program takes over blacklist control.
+    pub fn set_gatekeeper_controller_program(
+        ctx: Context<SetGatekeeperControllerProgram>,
+        controller_program: Pubkey,
+    ) -> Result<()> {
+        let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+        let controller_signer = &[&controller_seeds[..]];
+        let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+        let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::SetControllerProgram {
+            config: ctx.accounts.gatekeeper_config.to_account_info(),
+            authority: ctx.accounts.asset_protection.to_account_info(),
+            controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+        transfer_hook_gatekeeper::cpi::set_controller_program(cpi_ctx, controller_program)?;
+
+        Ok(())
+    }
+
+    /// @dev Wipes tokens from a blacklisted address using the Permanent Delegate power.
+    pub fn wipe_blacklisted_address(ctx: Context<WipeAddress>, amount: u64) -> Result<()> {
+        require!(amount > 0, GoldTokenError::InvalidAmount);
+        require!(
+            !ctx.accounts.blacklist_entry.data_is_empty(),
+            GoldTokenError::AddressNotBlacklisted
+        );
+        require!(
+            ctx.accounts.target_token_account.amount >= amount,
+            GoldTokenError::InsufficientBalance
+        );
+        let emptied = ctx.accounts.target_token_account.amount == amount;
+        
+        burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.target_token_account.to_account_info(),
+                    authority: ctx.accounts.asset_protection.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        // Burns skip the transfer hook, so stop counting a wallet left empty
+        if emptied {
+            let controller_seeds = &[CONTROLLER_AUTHORITY_SEED, &[ctx.bumps.gatekeeper_controller]];
+            let controller_signer = &[&controller_seeds[..]];
+            let cpi_program = ctx.accounts.gatekeeper_program.to_account_info();
+            let cpi_accounts = transfer_hook_gatekeeper::cpi::accounts::UpdateHolderCount {
+                config: ctx.accounts.gatekeeper_config.to_account_info(),
+                controller: ctx.accounts.gatekeeper_controller.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, controller_signer);
+            transfer_hook_gatekeeper::cpi::remove_holder(cpi_ctx)?;
+        }
+
+        emit!(TokensWiped {
+            target_user: *ctx.accounts.target_user.key,
+            amount,
+            authority: *ctx.accounts.asset_protection.key,
+        });
+
+        Ok(())
+    }
+}
+
+
+#[derive(Accounts)]
+#[instruction(name: String, symbol: String, uri: String, transfer_fee_basis_points: u16, maximum_fee: u64)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: Authority account, constrained at mint creation.
+    pub supply_controller: AccountInfo<'info>,
+    /// Asset protection authority - must be signer for gatekeeper initialization
+    pub asset_protection: Signer<'info>,
+    /// CHECK: Authority account, constrained at mint creation.
+    pub fee_controller: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+
+    /// Only the upgrade authority may claim the singleton config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::GoldToken>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ GoldTokenError::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(
+        init, 
+        payer = admin, 
+        space = Config::LEN,
+        seeds = [b"config"], 
+        bump
+    )]
     pub config: Account<'info, Config>,
 
     /// CHECK: This is the config account for the gatekeeper program.
@@ -632,6 +1517,175 @@ pub struct UpdateRole<'info> {
     pub admin: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ResizeConfig<'info> {
+    /// CHECK: Deserialized by hand, since older layouts are shorter than `Config::LEN`.
+    #[account(mut, owner = crate::ID, seeds = [b"config"], bump)]
+    pub config: AccountInfo<'info>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(mut, has_one = auditor)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = auditor,
+        space = 8 + 8 + 8 + 32 + 32, // discriminator + vault_grams + attested_at + report_hash + auditor
+        seeds = [b"reserve_attestation"],
+        bump
+    )]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitSignedAttestation<'info> {
+    #[account(mut)]
+    pub config: Account<'info, Config>,
+    /// Any relayer; the report's authority comes from the Ed25519 signature
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"auditor_registry"], bump)]
+    pub auditor_registry: Account<'info, AuditorRegistry>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 8 + 8 + 32 + 32, // discriminator + vault_grams + attested_at + report_hash + auditor
+        seeds = [b"reserve_attestation"],
+        bump
+    )]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+    /// CHECK: The instructions sysvar, used to inspect the Ed25519 verification
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct CreateVault<'info> {
+    #[account(has_one = custodian)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    #[account(
+        init,
+        payer = custodian,
+        space = 8 + 8 + 4 + MAX_VAULT_LOCATION_LEN + 8 + 4, // discriminator + vault_id + location + fine_weight_mg + bar_count
+        seeds = [b"vault".as_ref(), &vault_id.to_le_bytes()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(refiner: String, serial: String)]
+pub struct RegisterGoldBar<'info> {
+    #[account(has_one = custodian)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = custodian,
+        space = 8 + (4 + MAX_BAR_FIELD_LEN) * 2 + 8 + 2 + 8 + 32 + 1 + 8, // discriminator + refiner + serial + weights + fineness + vault + status + registered_at
+        seeds = [b"gold_bar", refiner.as_bytes(), serial.as_bytes()],
+        bump
+    )]
+    pub gold_bar: Account<'info, GoldBar>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterBarDeposit<'info> {
+    #[account(has_one = custodian)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub custodian: Signer<'info>,
+    #[account(constraint = gold_bar.status == BarStatus::InVault @ GoldTokenError::InvalidBarStatus)]
+    pub gold_bar: Account<'info, GoldBar>,
+    #[account(
+        init,
+        payer = custodian,
+        space = 8 + 32 + 4 + MAX_BAR_FIELD_LEN + 8 + 32 + 8 + 1, // discriminator + gold_bar + serial + fine_weight_mg + recipient + registered_at + consumed
+        seeds = [b"bar_deposit", gold_bar.key().as_ref()],
+        bump
+    )]
+    pub bar_deposit: Account<'info, BarDeposit>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateGoldBarStatus<'info> {
+    #[account(has_one = custodian)]
+    pub config: Account<'info, Config>,
+    pub custodian: Signer<'info>,
+    #[account(mut, has_one = vault)]
+    pub gold_bar: Account<'info, GoldBar>,
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct MoveGoldBar<'info> {
+    #[account(has_one = custodian)]
+    pub config: Account<'info, Config>,
+    pub custodian: Signer<'info>,
+    #[account(mut, constraint = gold_bar.vault != to_vault.key() @ GoldTokenError::InvalidBarStatus)]
+    pub gold_bar: Account<'info, GoldBar>,
+    pub to_vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct AddAuditorKey<'info> {
+    #[account(has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + 4 + 32 * MAX_AUDITOR_KEYS, // discriminator + vec len + auditor keys
+        seeds = [b"auditor_registry"],
+        bump
+    )]
+    pub auditor_registry: Account<'info, AuditorRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAuditorKey<'info> {
+    #[account(has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"auditor_registry"], bump)]
+    pub auditor_registry: Account<'info, AuditorRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct AcknowledgeReserveRecovery<'info> {
+    #[account(mut, has_one = admin)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(seeds = [b"reserve_attestation"], bump)]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+}
+
 #[derive(Accounts)]
 pub struct TogglePause<'info> {
     #[account(mut, has_one = admin)]
@@ -639,6 +1693,27 @@ pub struct TogglePause<'info> {
     pub admin: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ReleaseLockup<'info> {
+    #[account(has_one = admin)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The wallet whose lockup is released.
+    pub wallet: AccountInfo<'info>,
+    /// CHECK: The wallet's lockup, closed by the gatekeeper.
+    #[account(mut)]
+    pub lockup: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper lockups.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program.
+    pub gatekeeper_config: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetTransferFee<'info> {
     #[account(has_one = fee_controller)]
@@ -692,8 +1767,7 @@ pub struct WithdrawWithheldTokensFromAccounts<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(amount: u64, recipient: Pubkey)]
-pub struct MintTokens<'info> {
+pub struct MintAgainstDeposit<'info> {
     #[account(has_one = supply_controller)]
     pub config: Account<'info, Config>,
     #[account(mut)]
@@ -704,8 +1778,21 @@ pub struct MintTokens<'info> {
     /// CHECK: PDA mint authority.
     pub mint_authority_pda: AccountInfo<'info>,
     
+    #[account(
+        mut,
+        has_one = recipient,
+        constraint = !bar_deposit.consumed @ GoldTokenError::DepositAlreadyConsumed
+    )]
+    pub bar_deposit: Account<'info, BarDeposit>,
+    /// The deposited bar, which must still be in the vault
+    #[account(
+        address = bar_deposit.gold_bar,
+        constraint = gold_bar.status == BarStatus::InVault @ GoldTokenError::InvalidBarStatus
+    )]
+    pub gold_bar: Account<'info, GoldBar>,
+
     /// The recipient who will own the tokens
-    /// CHECK: Any valid Solana address can receive tokens
+    /// CHECK: Must match the deposit's recipient
     pub recipient: AccountInfo<'info>,
     
     /// Associated Token Account for the recipient
@@ -717,6 +1804,20 @@ pub struct MintTokens<'info> {
         associated_token::token_program = token_program,
     )]
     pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: The recipient's lockup, created or extended by the gatekeeper.
+    #[account(mut)]
+    pub lockup: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper lockups and holder counts.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program, which counts holders.
+    #[account(mut)]
+    pub gatekeeper_config: AccountInfo<'info>,
+
+    #[account(seeds = [b"reserve_attestation"], bump)]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
     
     pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -772,6 +1873,13 @@ pub struct FulfillRedemption<'info> {
     /// CHECK: PDA that was delegated authority.
     pub redemption_pda: AccountInfo<'info>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper holder counts.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program, which counts holders.
+    #[account(mut)]
+    pub gatekeeper_config: AccountInfo<'info>,
 }
 
 #[derive(Accounts)]
@@ -794,6 +1902,70 @@ pub struct UpdateRedemptionStatus<'info> {
     pub redemption_request: Account<'info, RedemptionRequest>,
 }
 
+#[derive(Accounts)]
+#[instruction(review_id: u64)]
+pub struct TransferViaReview<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    #[account(has_one = mint)]
+    pub config: Account<'info, Config>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = mint, token::authority = sender)]
+    pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: The wallet that receives the tokens once the review approves them.
+    pub destination: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 8 + 32 + 32 + 8 + 8, // discriminator + review_id + sender + destination + amount + requested_at
+        seeds = [b"review_transfer", sender.key().as_ref(), &review_id.to_le_bytes()],
+        bump
+    )]
+    pub review_transfer: Account<'info, ReviewTransfer>,
+    /// CHECK: PDA that holds every transfer awaiting review.
+    #[account(seeds = [REVIEW_ESCROW_SEED], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = sender,
+        associated_token::mint = mint,
+        associated_token::authority = escrow_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveReviewTransfer<'info> {
+    #[account(has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The sender, refunded the review account's rent.
+    #[account(mut)]
+    pub sender: AccountInfo<'info>,
+    #[account(mut, close = sender, has_one = sender)]
+    pub review_transfer: Account<'info, ReviewTransfer>,
+    /// CHECK: PDA that holds every transfer awaiting review.
+    #[account(seeds = [REVIEW_ESCROW_SEED], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = escrow_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// The destination's token account on approval, the sender's on rejection
+    #[account(mut, token::mint = mint)]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateBlacklist<'info> {
     #[account(has_one = asset_protection)]
@@ -807,6 +1979,9 @@ pub struct UpdateBlacklist<'info> {
     #[account(mut)]
     pub blacklist_entry: AccountInfo<'info>,
     pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper blacklist changes.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
     /// CHECK: The config account for the gatekeeper program.
     #[account(mut)]
     pub gatekeeper_config: AccountInfo<'info>,
@@ -826,6 +2001,108 @@ pub struct RemoveBlacklist<'info> {
     #[account(mut)]
     pub blacklist_entry: AccountInfo<'info>,
     pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper blacklist changes.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program.
+    #[account(mut)]
+    pub gatekeeper_config: AccountInfo<'info>,
+}
+
+/// Remaining accounts: `[target, blacklist entry]` pairs, entries writable.
+#[derive(Accounts)]
+pub struct UpdateBlacklistBatch<'info> {
+    #[account(has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper blacklist changes.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program.
+    pub gatekeeper_config: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateBlacklist<'info> {
+    #[account(has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The address whose entry is migrated.
+    pub target_address: AccountInfo<'info>,
+    /// CHECK: The legacy global blacklist entry, copied by the gatekeeper.
+    pub legacy_blacklist_entry: AccountInfo<'info>,
+    /// CHECK: The per-mint blacklist entry, created by the gatekeeper.
+    #[account(mut)]
+    pub blacklist_entry: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper blacklist changes.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program.
+    pub gatekeeper_config: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLegacyBlacklist<'info> {
+    #[account(has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The address whose legacy entry is closed.
+    pub target_address: AccountInfo<'info>,
+    /// CHECK: The legacy global blacklist entry, closed by the gatekeeper.
+    #[account(mut)]
+    pub legacy_blacklist_entry: AccountInfo<'info>,
+    /// CHECK: The per-mint blacklist entry, checked by the gatekeeper.
+    pub blacklist_entry: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper blacklist changes.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program.
+    pub gatekeeper_config: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: The ExtraAccountMetaList, rewritten by the gatekeeper.
+    #[account(mut)]
+    pub extra_account_meta_list: AccountInfo<'info>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: The config account for the gatekeeper program.
+    pub gatekeeper_config: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetGatekeeperControllerProgram<'info> {
+    #[account(has_one = admin, has_one = asset_protection)]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+    pub asset_protection: Signer<'info>,
+    #[account(address = config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs the hand-over as the current controller.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
     /// CHECK: The config account for the gatekeeper program.
     #[account(mut)]
     pub gatekeeper_config: AccountInfo<'info>,
@@ -843,13 +2120,20 @@ pub struct WipeAddress<'info> {
     #[account(mut, token::mint = mint, token::authority = target_user)]
     pub target_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
-        seeds = [b"blacklist", target_user.key().as_ref()], 
+        seeds = [b"blacklist", mint.key().as_ref(), target_user.key().as_ref()], 
         bump, 
         seeds::program = config.gatekeeper_program
     )]
     /// CHECK: The PDA marker account for the blacklist entry.
     pub blacklist_entry: UncheckedAccount<'info>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub gatekeeper_program: Program<'info, TransferHookGatekeeper>,
+    /// CHECK: PDA that co-signs gatekeeper holder counts.
+    #[account(seeds = [CONTROLLER_AUTHORITY_SEED], bump)]
+    pub gatekeeper_controller: AccountInfo<'info>,
+    /// CHECK: The config account for the gatekeeper program, which counts holders.
+    #[account(mut)]
+    pub gatekeeper_config: AccountInfo<'info>,
 }
 
 // ============================================
@@ -866,7 +2150,101 @@ pub struct Config {
     pub mint: Pubkey,
     pub gatekeeper_program: Pubkey,
     pub redemption_request_counter: u64,
+    /// Read by the gatekeeper transfer hook at a fixed offset; append new fields after it.
     pub is_paused: bool,
+    /// Writes the reserve attestation minting is checked against
+    pub auditor: Pubkey,
+    /// Hard cap on total supply in base units, 0 for no cap
+    pub supply_cap: u64,
+    /// Seconds a reserve attestation stays usable for minting
+    pub reserve_staleness_window: i64,
+    /// Time of the attestation that found a shortfall, 0 while reserves are healthy
+    pub reserve_shortfall_at: i64,
+    /// Registers vaults and gold bars and reports their status
+    pub custodian: Pubkey,
+    /// Reserve attestations recorded so far
+    pub reserve_attestation_count: u64,
+    /// Value of `reserve_attestation_count` when the active shortfall was found
+    pub reserve_shortfall_sequence: u64,
+}
+
+impl Config {
+    pub const LEN: usize = 8 + 32*6 + 8 + 1 + 32 + 8 + 8 + 8 + 32 + 8 + 8;
+}
+
+/// Latest audited count of the vaulted gold; each whole token is backed by one gram.
+#[account]
+pub struct ReserveAttestation {
+    pub vault_grams: u64,
+    pub attested_at: i64,
+    /// Hash of the auditor's signed reserve report
+    pub report_hash: [u8; 32],
+    pub auditor: Pubkey,
+}
+
+impl ReserveAttestation {
+    /// Stores a new count and flags a shortfall on `config` if it no longer backs the supply
+    fn record(
+        &mut self,
+        config: &mut Config,
+        mint: &Mint,
+        vault_grams: u64,
+        report_hash: [u8; 32],
+        attested_at: i64,
+        auditor: Pubkey,
+    ) -> Result<()> {
+        self.vault_grams = vault_grams;
+        self.attested_at = attested_at;
+        self.report_hash = report_hash;
+        self.auditor = auditor;
+        config.reserve_attestation_count = config.reserve_attestation_count.saturating_add(1);
+
+        emit!(ReservesAttested {
+            vault_grams,
+            report_hash,
+            auditor,
+            attested_at,
+        });
+
+        // An under-backed supply restricts minting until the admin acknowledges a healthy attestation
+        if mint.supply > self.backed_supply(mint.decimals) && config.reserve_shortfall_at == 0 {
+            config.reserve_shortfall_at = attested_at;
+            config.reserve_shortfall_sequence = config.reserve_attestation_count;
+
+            emit!(ReserveShortfall {
+                vault_grams,
+                supply: mint.supply,
+                attested_at,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Largest supply in base units the attested grams can back
+    pub fn backed_supply(&self, decimals: u8) -> u64 {
+        10u64
+            .checked_pow(decimals as u32)
+            .and_then(|unit| self.vault_grams.checked_mul(unit))
+            .unwrap_or(u64::MAX)
+    }
+}
+
+pub const MAX_AUDITOR_KEYS: usize = 8;
+
+/// Keys allowed to sign reserve reports off-chain.
+#[account]
+pub struct AuditorRegistry {
+    pub auditors: Vec<Pubkey>,
+}
+
+/// Reserve report as signed by an auditor key; the signed message is its Borsh encoding.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ReserveReport {
+    pub mint: Pubkey,
+    pub vault_grams: u64,
+    pub report_hash: [u8; 32],
+    pub reported_at: i64,
 }
 
 #[account]
@@ -888,6 +2266,64 @@ pub enum RedemptionStatus {
     Cancelled,
 }
 
+pub const MAX_VAULT_LOCATION_LEN: usize = 64;
+/// Refiner names and serials are PDA seeds, so they share the seed length limit.
+pub const MAX_BAR_FIELD_LEN: usize = 32;
+/// Fineness is expressed in parts per 10,000 (9999 = 99.99% pure)
+pub const FINENESS_SCALE: u16 = 10_000;
+
+pub const MG_PER_GRAM: u64 = 1_000;
+
+/// Physical vault; totals cover only bars currently `InVault` there.
+#[account]
+pub struct Vault {
+    pub vault_id: u64,
+    pub location: String,
+    pub fine_weight_mg: u64,
+    pub bar_count: u32,
+}
+
+#[account]
+pub struct GoldBar {
+    pub refiner: String,
+    pub serial: String,
+    pub gross_weight_mg: u64,
+    pub fineness: u16,
+    pub fine_weight_mg: u64,
+    pub vault: Pubkey,
+    pub status: BarStatus,
+    pub registered_at: i64,
+}
+
+/// One per bar ever, so a bar can back at most one mint.
+#[account]
+pub struct BarDeposit {
+    pub gold_bar: Pubkey,
+    pub serial: String,
+    pub fine_weight_mg: u64,
+    pub recipient: Pubkey,
+    pub registered_at: i64,
+    pub consumed: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum BarStatus {
+    InVault,
+    InTransit,
+    Redeemed,
+}
+
+/// Transfer held in escrow until asset protection approves or rejects it; closed on resolution.
+#[account]
+pub struct ReviewTransfer {
+    pub review_id: u64,
+    pub sender: Pubkey,
+    pub destination: Pubkey,
+    /// Amount held in escrow, net of the deposit's transfer fee
+    pub amount: u64,
+    pub requested_at: i64,
+}
+
 #[event]
 pub struct TokenInitialized {
     pub mint: Pubkey,
@@ -902,12 +2338,105 @@ pub struct RoleUpdated {
     pub new_authority: Pubkey,
 }
 
+#[event]
+pub struct ReserveLimitsUpdated {
+    pub supply_cap: u64,
+    pub reserve_staleness_window: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ReservesAttested {
+    pub vault_grams: u64,
+    pub report_hash: [u8; 32],
+    pub auditor: Pubkey,
+    pub attested_at: i64,
+}
+
+#[event]
+pub struct ReserveShortfall {
+    pub vault_grams: u64,
+    pub supply: u64,
+    pub attested_at: i64,
+}
+
+#[event]
+pub struct ReserveShortfallCleared {
+    pub vault_grams: u64,
+    pub attested_at: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AuditorKeyUpdated {
+    pub auditor_key: Pubkey,
+    pub registered: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct VaultCreated {
+    pub vault: Pubkey,
+    pub vault_id: u64,
+    pub location: String,
+}
+
+#[event]
+pub struct GoldBarRegistered {
+    pub gold_bar: Pubkey,
+    pub refiner: String,
+    pub serial: String,
+    pub fine_weight_mg: u64,
+    pub vault: Pubkey,
+}
+
+#[event]
+pub struct GoldBarStatusChanged {
+    pub gold_bar: Pubkey,
+    pub old_status: BarStatus,
+    pub new_status: BarStatus,
+}
+
+#[event]
+pub struct GoldBarMoved {
+    pub gold_bar: Pubkey,
+    pub from_vault: Pubkey,
+    pub to_vault: Pubkey,
+}
+
+#[event]
+pub struct BarDepositRegistered {
+    pub bar_deposit: Pubkey,
+    pub gold_bar: Pubkey,
+    pub serial: String,
+    pub fine_weight_mg: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct DepositMinted {
+    pub bar_deposit: Pubkey,
+    pub gold_bar: Pubkey,
+    pub serial: String,
+    pub fine_weight_mg: u64,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
 #[event]
 pub struct PauseToggled {
     pub is_paused: bool,
     pub authority: Pubkey,
 }
 
+#[event]
+pub struct LockupReleased {
+    pub wallet: Pubkey,
+    pub locked_amount: u64,
+    pub unlock_at: i64,
+    pub authority: Pubkey,
+}
+
 #[event]
 pub struct TokensMinted {
     pub mint: Pubkey,
@@ -949,16 +2478,76 @@ pub struct RedemptionStatusUpdated {
     pub new_status: RedemptionStatus,
 }
 
+#[event]
+pub struct ReviewTransferRequested {
+    pub review_id: u64,
+    pub sender: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewTransferApproved {
+    pub review_id: u64,
+    pub sender: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReviewTransferRejected {
+    pub review_id: u64,
+    pub sender: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct AddressBlacklisted {
     pub address: Pubkey,
     pub authority: Pubkey,
+    pub reason_code: u16,
+    pub case_reference: [u8; 32],
+    pub added_at: i64,
 }
 
 #[event]
 pub struct AddressUnblacklisted {
     pub address: Pubkey,
     pub authority: Pubkey,
+    pub reason_code: u16,
+    pub case_reference: [u8; 32],
+    pub added_by: Pubkey,
+    pub added_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BlacklistCase {
+    pub address: Pubkey,
+    pub reason_code: u16,
+    pub case_reference: [u8; 32],
+    pub added_by: Pubkey,
+    pub added_at: i64,
+}
+
+#[event]
+pub struct AddressesBlacklisted {
+    pub addresses: Vec<Pubkey>,
+    pub authority: Pubkey,
+    pub reason_code: u16,
+    pub case_reference: [u8; 32],
+    pub added_at: i64,
+}
+
+#[event]
+pub struct AddressesUnblacklisted {
+    pub entries: Vec<BlacklistCase>,
+    pub authority: Pubkey,
 }
 
 #[event]
@@ -1011,4 +2600,42 @@ pub enum GoldTokenError {
     Unauthorized,
     #[msg("Contract is paused.")]
     ContractPaused,
+    #[msg("Batch accounts must be [target, blacklist entry] pairs.")]
+    InvalidBatch,
+    #[msg("The token account does not belong to the review's recipient.")]
+    InvalidReviewRecipient,
+    #[msg("Config account is not a gold token config.")]
+    InvalidConfig,
+    #[msg("The reserve staleness window must be positive.")]
+    InvalidStalenessWindow,
+    #[msg("Minting would exceed the supply cap.")]
+    SupplyCapExceeded,
+    #[msg("The reserve attestation is too old.")]
+    StaleReserveAttestation,
+    #[msg("Minting would exceed the attested reserves.")]
+    InsufficientReserves,
+    #[msg("Minting is restricted until the reserve shortfall is cleared.")]
+    ReserveShortfallActive,
+    #[msg("There is no reserve shortfall to clear.")]
+    NoReserveShortfall,
+    #[msg("The reserve report is for another mint or is not newer than the last attestation.")]
+    InvalidReserveReport,
+    #[msg("Expected an Ed25519 verification of the reserve report before this instruction.")]
+    InvalidEd25519Instruction,
+    #[msg("The report was not signed by a registered auditor key.")]
+    UnknownAuditorKey,
+    #[msg("The auditor registry is full.")]
+    AuditorRegistryFull,
+    #[msg("Vault location is empty or too long.")]
+    InvalidVaultDetails,
+    #[msg("Invalid gold bar refiner, serial, weight or fineness.")]
+    InvalidBarDetails,
+    #[msg("Invalid gold bar status for this action.")]
+    InvalidBarStatus,
+    #[msg("This deposit has already been minted.")]
+    DepositAlreadyConsumed,
+    #[msg("The deposit's fine weight does not fit in a token amount.")]
+    DepositAmountOverflow,
+    #[msg("Legacy blacklist entries can only be migrated while the token is paused.")]
+    MigrationRequiresPause,
 }
\ No newline at end of file
@@ -0,0 +1,112 @@
+//! Batch blacklist changes are checked for whole `[target, entry]` pairs before
+//! they reach the gatekeeper, and legacy entries only migrate while the token is paused.
+
+mod common;
+
+use anchor_lang::{prelude::*, InstructionData};
+use common::{
+    account_info, gold_token_error, install_stubs, mint_account, program_info, signer_info,
+    state_account, system_program,
+};
+use gold_token::{instruction, Config, GoldTokenError, ID};
+use transfer_hook_gatekeeper::CONTROLLER_AUTHORITY_SEED;
+
+fn add_batch(targets: usize, extra: usize) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let config = Config {
+        asset_protection: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        ..Config::default()
+    };
+    let controller = Pubkey::find_program_address(&[CONTROLLER_AUTHORITY_SEED], &ID).0;
+    let mut accounts = vec![
+        state_account(Pubkey::new_unique(), &config),
+        signer_info(config.asset_protection),
+        mint_account(config.mint),
+        program_info(transfer_hook_gatekeeper::ID),
+        account_info(controller, System::id(), vec![]),
+        account_info(Pubkey::new_unique(), transfer_hook_gatekeeper::ID, vec![1]),
+        system_program(),
+    ];
+    for _ in 0..targets * 2 + extra {
+        accounts.push(account_info(Pubkey::new_unique(), System::id(), vec![]));
+    }
+    gold_token::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::AddToBlacklistBatch {
+            reason_code: 7,
+            case_reference: [3; 32],
+        }
+        .data(),
+    )
+}
+
+#[test]
+fn whole_pairs_are_forwarded() {
+    assert_eq!(add_batch(2, 0), Ok(()));
+}
+
+#[test]
+fn unpaired_target_is_rejected() {
+    assert_eq!(
+        add_batch(2, 1),
+        Err(gold_token_error(GoldTokenError::InvalidBatch))
+    );
+}
+
+#[test]
+fn empty_batch_is_rejected() {
+    assert_eq!(
+        add_batch(0, 0),
+        Err(gold_token_error(GoldTokenError::InvalidBatch))
+    );
+}
+
+/// Migrate a legacy entry with the token paused or not
+fn migrate(is_paused: bool) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let config = Config {
+        asset_protection: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        is_paused,
+        ..Config::default()
+    };
+    let controller = Pubkey::find_program_address(&[CONTROLLER_AUTHORITY_SEED], &ID).0;
+    let mut blacklist_entry = account_info(Pubkey::new_unique(), System::id(), vec![]);
+    blacklist_entry.is_writable = true;
+    let accounts = vec![
+        state_account(Pubkey::new_unique(), &config),
+        signer_info(config.asset_protection),
+        mint_account(config.mint),
+        account_info(Pubkey::new_unique(), System::id(), vec![]),
+        account_info(Pubkey::new_unique(), transfer_hook_gatekeeper::ID, vec![1]),
+        blacklist_entry,
+        program_info(transfer_hook_gatekeeper::ID),
+        account_info(controller, System::id(), vec![]),
+        account_info(Pubkey::new_unique(), transfer_hook_gatekeeper::ID, vec![1]),
+        system_program(),
+    ];
+    gold_token::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::MigrateBlacklistEntry {
+            reason_code: 7,
+            case_reference: [3; 32],
+        }
+        .data(),
+    )
+}
+
+#[test]
+fn legacy_entry_migrates_while_paused() {
+    assert_eq!(migrate(true), Ok(()));
+}
+
+#[test]
+fn legacy_entry_cannot_migrate_while_transfers_run() {
+    assert_eq!(
+        migrate(false),
+        Err(gold_token_error(GoldTokenError::MigrationRequiresPause))
+    );
+}
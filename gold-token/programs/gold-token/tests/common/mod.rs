@@ -0,0 +1,235 @@
+//! Helpers for driving the gold token entrypoint without a validator.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+        instruction::Instruction,
+        program_option::COption,
+        program_pack::Pack,
+        program_stubs,
+    },
+};
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, ID as ASSOCIATED_TOKEN_ID},
+    token_2022::{
+        spl_token_2022::state::{Account as TokenAccount, AccountState, Mint},
+        ID as TOKEN_2022_PROGRAM_ID,
+    },
+};
+
+/// Unix timestamp every test observes through `Clock::get`
+pub const NOW: i64 = 1_760_000_000;
+
+/// Supply of every test mint, 1_000 whole tokens
+pub const SUPPLY: u64 = 1_000_000_000_000;
+
+pub const DECIMALS: u8 = 9;
+
+/// Token-2022 instruction tag of `TransferChecked`
+const TRANSFER_CHECKED: u8 = 12;
+
+thread_local! {
+    /// Data of every gatekeeper instruction invoked on this test's thread
+    static GATEKEEPER_CALLS: std::cell::RefCell<Vec<Vec<u8>>> = const {
+        std::cell::RefCell::new(vec![])
+    };
+}
+
+/// Data of the gatekeeper instructions invoked since the last call
+pub fn gatekeeper_calls() -> Vec<Vec<u8>> {
+    GATEKEEPER_CALLS.with(|calls| calls.take())
+}
+
+struct Stubs;
+
+impl program_stubs::SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    /// Emulates `system_program::create_account` so `init` constraints and
+    /// hand-created PDAs succeed, and Token-2022 `TransferChecked` so balances move.
+    /// Gatekeeper instructions are recorded; every other CPI is a no-op
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let data = &instruction.data;
+        let find = |key: &Pubkey| account_infos.iter().find(|info| info.key == key).unwrap();
+        if instruction.program_id == transfer_hook_gatekeeper::ID {
+            GATEKEEPER_CALLS.with(|calls| calls.borrow_mut().push(data.clone()));
+            return Ok(());
+        }
+        if instruction.program_id == TOKEN_2022_PROGRAM_ID && data[0] == TRANSFER_CHECKED {
+            let amount = u64::from_le_bytes(data[1..9].try_into().unwrap());
+            let (source, destination) = (
+                find(&instruction.accounts[0].pubkey),
+                find(&instruction.accounts[2].pubkey),
+            );
+            let balance = |info: &AccountInfo| {
+                u64::from_le_bytes(info.try_borrow_data().unwrap()[64..72].try_into().unwrap())
+            };
+            let (from, to) = (balance(source) - amount, balance(destination) + amount);
+            source.try_borrow_mut_data()?[64..72].copy_from_slice(&from.to_le_bytes());
+            destination.try_borrow_mut_data()?[64..72].copy_from_slice(&to.to_le_bytes());
+            return Ok(());
+        }
+        if instruction.program_id != System::id() || data[..4] != [0; 4] {
+            return Ok(());
+        }
+        let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+        let space = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        let owner = Pubkey::try_from(&data[20..52]).unwrap();
+        let (payer, created) = (
+            find(&instruction.accounts[0].pubkey),
+            find(&instruction.accounts[1].pubkey),
+        );
+        **payer.try_borrow_mut_lamports()? -= lamports;
+        **created.try_borrow_mut_lamports()? += lamports;
+        created.assign(&owner);
+        created.resize(space as usize)
+    }
+}
+
+pub fn install_stubs() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+pub fn leak<T>(value: T) -> &'static mut T {
+    Box::leak(Box::new(value))
+}
+
+/// Lays `key` and `data` out the way the runtime serializes them, so `resize` can
+/// record the new length in front of the data and grow into spare room
+fn serialized(key: Pubkey, data: Vec<u8>) -> (&'static Pubkey, &'static mut [u8]) {
+    #[repr(C)]
+    struct SerializedKey {
+        original_data_len: u32,
+        key: Pubkey,
+    }
+    let header = leak(SerializedKey {
+        original_data_len: data.len() as u32,
+        key,
+    });
+    let words = (8 + data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+    let buffer = Box::leak(vec![0u64; words].into_boxed_slice());
+    buffer[0] = data.len() as u64;
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(1) as *mut u8, data.len())
+    };
+    bytes.copy_from_slice(&data);
+    (&header.key, bytes)
+}
+
+pub fn account_info(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    let lamports = if data.is_empty() { 0 } else { 1_000_000_000 };
+    let (key, data) = serialized(key, data);
+    AccountInfo::new(
+        key,
+        false,
+        false,
+        leak(lamports),
+        data,
+        leak(owner),
+        false,
+        0,
+    )
+}
+
+pub fn signer_info(key: Pubkey) -> AccountInfo<'static> {
+    let (key, data) = serialized(key, vec![]);
+    AccountInfo::new(
+        key,
+        true,
+        true,
+        leak(1_000_000_000),
+        data,
+        leak(System::id()),
+        false,
+        0,
+    )
+}
+
+/// Writable account holding `state` owned by the gold token program
+pub fn state_account<T: AccountSerialize>(key: Pubkey, state: &T) -> AccountInfo<'static> {
+    let mut data = vec![];
+    state.try_serialize(&mut data).unwrap();
+    let mut info = account_info(key, gold_token::ID, data);
+    info.is_writable = true;
+    info
+}
+
+pub fn program_info(key: Pubkey) -> AccountInfo<'static> {
+    let mut info = account_info(key, Pubkey::default(), vec![]);
+    info.executable = true;
+    info
+}
+
+pub fn system_program() -> AccountInfo<'static> {
+    program_info(System::id())
+}
+
+pub fn token_program() -> AccountInfo<'static> {
+    program_info(TOKEN_2022_PROGRAM_ID)
+}
+
+pub fn associated_token_program() -> AccountInfo<'static> {
+    program_info(ASSOCIATED_TOKEN_ID)
+}
+
+pub fn mint_account(key: Pubkey) -> AccountInfo<'static> {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: SUPPLY,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    let mut info = account_info(key, TOKEN_2022_PROGRAM_ID, data);
+    info.is_writable = true;
+    info
+}
+
+/// `owner`'s associated token account for `mint` holding `amount`
+pub fn token_account(mint: Pubkey, owner: Pubkey, amount: u64) -> AccountInfo<'static> {
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint,
+        owner,
+        amount,
+        delegate: COption::None,
+        state: AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    let key = get_associated_token_address_with_program_id(&owner, &mint, &TOKEN_2022_PROGRAM_ID);
+    let mut info = account_info(key, TOKEN_2022_PROGRAM_ID, data);
+    info.is_writable = true;
+    info
+}
+
+pub fn gold_token_error(error: gold_token::GoldTokenError) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
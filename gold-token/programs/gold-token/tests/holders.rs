@@ -0,0 +1,136 @@
+//! Burns skip the transfer hook, so redemptions and wipes that empty a wallet report
+//! it to the gatekeeper's holder count.
+
+mod common;
+
+use anchor_lang::{prelude::*, InstructionData};
+use common::{
+    account_info, gatekeeper_calls, install_stubs, mint_account, program_info, signer_info,
+    state_account, token_account, token_program,
+};
+use gold_token::{instruction, Config, RedemptionRequest, RedemptionStatus, ID};
+use transfer_hook_gatekeeper::{
+    instruction as gatekeeper_instruction, BlacklistEntry, CONTROLLER_AUTHORITY_SEED,
+};
+
+/// Amount every test redeems or wipes
+const AMOUNT: u64 = 1_000;
+
+fn config() -> Config {
+    Config {
+        supply_controller: Pubkey::new_unique(),
+        asset_protection: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        gatekeeper_program: transfer_hook_gatekeeper::ID,
+        ..Config::default()
+    }
+}
+
+/// Controller PDA, gatekeeper program and gatekeeper config, in instruction order
+fn gatekeeper_accounts() -> Vec<AccountInfo<'static>> {
+    let controller = Pubkey::find_program_address(&[CONTROLLER_AUTHORITY_SEED], &ID).0;
+    let mut gatekeeper_config =
+        account_info(Pubkey::new_unique(), transfer_hook_gatekeeper::ID, vec![1]);
+    gatekeeper_config.is_writable = true;
+    vec![
+        program_info(transfer_hook_gatekeeper::ID),
+        account_info(controller, System::id(), vec![]),
+        gatekeeper_config,
+    ]
+}
+
+/// Fulfill a redemption of `AMOUNT` from a wallet holding `balance`, returning whether
+/// the wallet left the holder count
+fn fulfill(balance: u64) -> bool {
+    install_stubs();
+    let config = config();
+    let user = Pubkey::new_unique();
+    let request_id = 1u64;
+    let (redemption_pda, bump) = Pubkey::find_program_address(
+        &[b"redemption_pda", user.as_ref(), &request_id.to_le_bytes()],
+        &ID,
+    );
+    let request = RedemptionRequest {
+        user,
+        amount: AMOUNT,
+        status: RedemptionStatus::Pending,
+        requested_at: 0,
+        completed_at: 0,
+        request_id,
+        redemption_pda_bump: bump,
+    };
+    let mut accounts = vec![
+        state_account(Pubkey::new_unique(), &config),
+        signer_info(config.supply_controller),
+        state_account(Pubkey::new_unique(), &request),
+        mint_account(config.mint),
+        token_account(config.mint, user, balance),
+        account_info(user, System::id(), vec![]),
+        account_info(redemption_pda, System::id(), vec![]),
+        token_program(),
+    ];
+    accounts.extend(gatekeeper_accounts());
+    gatekeeper_calls();
+    gold_token::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::FulfillRedemption {}.data(),
+    )
+    .unwrap();
+    gatekeeper_calls().contains(&gatekeeper_instruction::RemoveHolder {}.data())
+}
+
+/// Wipe `AMOUNT` from a blacklisted wallet holding `balance`, returning whether the
+/// wallet left the holder count
+fn wipe(balance: u64) -> bool {
+    install_stubs();
+    let config = config();
+    let target = Pubkey::new_unique();
+    let entry = Pubkey::find_program_address(
+        &[b"blacklist", config.mint.as_ref(), target.as_ref()],
+        &transfer_hook_gatekeeper::ID,
+    )
+    .0;
+    let mut entry_data = vec![];
+    BlacklistEntry::default()
+        .try_serialize(&mut entry_data)
+        .unwrap();
+    let mut accounts = vec![
+        state_account(Pubkey::new_unique(), &config),
+        signer_info(config.asset_protection),
+        mint_account(config.mint),
+        account_info(target, System::id(), vec![]),
+        token_account(config.mint, target, balance),
+        account_info(entry, transfer_hook_gatekeeper::ID, entry_data),
+        token_program(),
+    ];
+    accounts.extend(gatekeeper_accounts());
+    gatekeeper_calls();
+    gold_token::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::WipeBlacklistedAddress { amount: AMOUNT }.data(),
+    )
+    .unwrap();
+    gatekeeper_calls().contains(&gatekeeper_instruction::RemoveHolder {}.data())
+}
+
+#[test]
+fn redemption_of_the_whole_balance_removes_the_holder() {
+    assert!(fulfill(AMOUNT));
+}
+
+#[test]
+fn partial_redemption_keeps_the_holder() {
+    assert!(!fulfill(AMOUNT + 1));
+}
+
+#[test]
+fn wipe_of_the_whole_balance_removes_the_holder() {
+    assert!(wipe(AMOUNT));
+}
+
+#[test]
+fn partial_wipe_keeps_the_holder() {
+    assert!(!wipe(AMOUNT + 1));
+}
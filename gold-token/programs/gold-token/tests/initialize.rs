@@ -0,0 +1,143 @@
+//! Only the program's upgrade authority can claim the singleton config.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, solana_program::sysvar, InstructionData};
+use common::{
+    account_info, gold_token_error, install_stubs, leak, program_info, signer_info, system_program,
+    token_program,
+};
+use gold_token::{instruction, Config, GoldTokenError, ID};
+use solana_loader_v3_interface::get_program_data_address;
+use solana_sdk_ids::bpf_loader_upgradeable;
+
+struct Deployment {
+    upgrade_authority: Pubkey,
+    program_data: Pubkey,
+}
+
+impl Deployment {
+    fn new() -> Self {
+        Self {
+            upgrade_authority: Pubkey::new_unique(),
+            program_data: get_program_data_address(&ID),
+        }
+    }
+
+    /// The upgradeable loader's `Program` account pointing at `program_data`
+    fn program(&self) -> AccountInfo<'static> {
+        let mut data = 2u32.to_le_bytes().to_vec();
+        data.extend_from_slice(self.program_data.as_ref());
+        let mut info = account_info(ID, bpf_loader_upgradeable::ID, data);
+        info.executable = true;
+        info
+    }
+
+    fn program_data_account(&self, key: Pubkey) -> AccountInfo<'static> {
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(self.upgrade_authority.as_ref());
+        account_info(key, bpf_loader_upgradeable::ID, data)
+    }
+
+    fn initialize(
+        &self,
+        admin: Pubkey,
+        program_data: AccountInfo<'static>,
+    ) -> std::result::Result<(), ProgramError> {
+        install_stubs();
+        let mint = Pubkey::new_unique();
+        let writable = |info: AccountInfo<'static>| {
+            let mut info = info;
+            info.is_writable = true;
+            info
+        };
+        let config = writable(account_info(
+            Pubkey::find_program_address(&[b"config"], &ID).0,
+            System::id(),
+            vec![0; Config::LEN],
+        ));
+        *config.lamports.borrow_mut() = leak(0);
+        let mut rent = 3480u64.to_le_bytes().to_vec();
+        rent.extend_from_slice(&2f64.to_le_bytes());
+        rent.push(50);
+        let accounts = vec![
+            signer_info(admin),
+            account_info(Pubkey::new_unique(), System::id(), vec![]),
+            signer_info(Pubkey::new_unique()),
+            account_info(Pubkey::new_unique(), System::id(), vec![]),
+            program_info(transfer_hook_gatekeeper::ID),
+            self.program(),
+            program_data,
+            config,
+            writable(account_info(Pubkey::new_unique(), System::id(), vec![])),
+            writable(account_info(
+                Pubkey::find_program_address(
+                    &[b"extra-account-metas", mint.as_ref()],
+                    &transfer_hook_gatekeeper::ID,
+                )
+                .0,
+                System::id(),
+                vec![],
+            )),
+            signer_info(mint),
+            account_info(
+                Pubkey::find_program_address(&[b"mint_authority"], &ID).0,
+                System::id(),
+                vec![],
+            ),
+            token_program(),
+            system_program(),
+            account_info(sysvar::rent::ID, sysvar::ID, rent),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::Initialize {
+                _name: "Gold".to_string(),
+                _symbol: "GOLD".to_string(),
+                _uri: String::new(),
+                transfer_fee_basis_points: 0,
+                maximum_fee: 0,
+            }
+            .data(),
+        )
+    }
+}
+
+#[test]
+fn upgrade_authority_claims_the_config() {
+    let deployment = Deployment::new();
+    let program_data = deployment.program_data_account(deployment.program_data);
+    assert_eq!(
+        deployment.initialize(deployment.upgrade_authority, program_data),
+        Ok(())
+    );
+}
+
+#[test]
+fn front_runner_cannot_claim_the_config() {
+    let deployment = Deployment::new();
+    let program_data = deployment.program_data_account(deployment.program_data);
+    assert_eq!(
+        deployment.initialize(Pubkey::new_unique(), program_data),
+        Err(gold_token_error(GoldTokenError::Unauthorized))
+    );
+}
+
+#[test]
+fn program_data_of_another_program_is_rejected() {
+    let deployment = Deployment::new();
+    // Program data of a program the hijacker deployed, naming them as upgrade authority
+    let hijacker = Pubkey::new_unique();
+    let forged = Deployment {
+        upgrade_authority: hijacker,
+        program_data: Pubkey::new_unique(),
+    };
+    let program_data = forged.program_data_account(forged.program_data);
+    assert_eq!(
+        deployment.initialize(hijacker, program_data),
+        Err(ProgramError::Custom(ErrorCode::ConstraintRaw.into()))
+    );
+}
@@ -0,0 +1,259 @@
+//! Minting is backed one gram per token by a registered bar that is still vaulted.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{
+    account_info, associated_token_program, gatekeeper_calls, gold_token_error, install_stubs,
+    mint_account, program_info, signer_info, state_account, system_program, token_account,
+    token_program, DECIMALS, NOW, SUPPLY,
+};
+use gold_token::{
+    instruction, BarDeposit, BarStatus, Config, GoldBar, GoldTokenError, ReserveAttestation, ID,
+};
+use transfer_hook_gatekeeper::{instruction as gatekeeper_instruction, CONTROLLER_AUTHORITY_SEED};
+
+/// Fine weight of a standard 400oz bar
+const FINE_WEIGHT_MG: u64 = 12_441_000;
+
+/// Base units minted for a standard bar, one token per gram
+const BAR_AMOUNT: u64 = FINE_WEIGHT_MG * 10u64.pow(DECIMALS as u32) / 1_000;
+
+struct Deposit {
+    config: Config,
+    recipient: Pubkey,
+    gold_bar: Pubkey,
+    bar_status: BarStatus,
+    fine_weight_mg: u64,
+    consumed: bool,
+    vault_grams: u64,
+    attested_at: i64,
+    /// Recipient balance before the mint
+    recipient_balance: u64,
+}
+
+impl Deposit {
+    fn new() -> Self {
+        Self {
+            config: Config {
+                supply_controller: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                gatekeeper_program: transfer_hook_gatekeeper::ID,
+                reserve_staleness_window: 86_400,
+                ..Config::default()
+            },
+            recipient: Pubkey::new_unique(),
+            gold_bar: Pubkey::new_unique(),
+            bar_status: BarStatus::InVault,
+            fine_weight_mg: FINE_WEIGHT_MG,
+            consumed: false,
+            vault_grams: 100_000,
+            attested_at: NOW,
+            recipient_balance: 0,
+        }
+    }
+
+    fn pda(seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &ID).0
+    }
+
+    fn gold_bar_account(&self) -> AccountInfo<'static> {
+        let bar = GoldBar {
+            refiner: "PAMP".to_string(),
+            serial: "AB12345".to_string(),
+            gross_weight_mg: FINE_WEIGHT_MG,
+            fineness: 9999,
+            fine_weight_mg: FINE_WEIGHT_MG,
+            vault: Pubkey::new_unique(),
+            status: self.bar_status.clone(),
+            registered_at: NOW,
+        };
+        state_account(self.gold_bar, &bar)
+    }
+
+    fn bar_deposit_account(&self) -> AccountInfo<'static> {
+        let deposit = BarDeposit {
+            gold_bar: self.gold_bar,
+            serial: "AB12345".to_string(),
+            fine_weight_mg: self.fine_weight_mg,
+            recipient: self.recipient,
+            registered_at: NOW,
+            consumed: self.consumed,
+        };
+        state_account(
+            Self::pda(&[b"bar_deposit", self.gold_bar.as_ref()]),
+            &deposit,
+        )
+    }
+
+    /// Mint against the deposit, handing back the deposit account on success
+    fn mint_with(
+        &self,
+        gold_bar: AccountInfo<'static>,
+    ) -> std::result::Result<BarDeposit, ProgramError> {
+        install_stubs();
+        let mint = self.config.mint;
+        let attestation = ReserveAttestation {
+            vault_grams: self.vault_grams,
+            attested_at: self.attested_at,
+            report_hash: [0; 32],
+            auditor: Pubkey::new_unique(),
+        };
+        let bar_deposit = self.bar_deposit_account();
+        let mut lockup = account_info(Pubkey::new_unique(), System::id(), vec![]);
+        lockup.is_writable = true;
+        let controller = Pubkey::find_program_address(&[CONTROLLER_AUTHORITY_SEED], &ID).0;
+        let mut gatekeeper_config =
+            account_info(Pubkey::new_unique(), transfer_hook_gatekeeper::ID, vec![1]);
+        gatekeeper_config.is_writable = true;
+        let accounts = vec![
+            state_account(Self::pda(&[b"config"]), &self.config),
+            signer_info(self.config.supply_controller),
+            mint_account(mint),
+            account_info(Self::pda(&[b"mint_authority"]), System::id(), vec![]),
+            bar_deposit.clone(),
+            gold_bar,
+            account_info(self.recipient, System::id(), vec![]),
+            token_account(mint, self.recipient, self.recipient_balance),
+            lockup,
+            program_info(transfer_hook_gatekeeper::ID),
+            account_info(controller, System::id(), vec![]),
+            gatekeeper_config,
+            state_account(Self::pda(&[b"reserve_attestation"]), &attestation),
+            token_program(),
+            associated_token_program(),
+            system_program(),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::MintAgainstDeposit {}.data(),
+        )?;
+        let data = bar_deposit.try_borrow_data()?;
+        Ok(BarDeposit::try_deserialize(&mut &data[..]).unwrap())
+    }
+
+    fn mint(&self) -> std::result::Result<BarDeposit, ProgramError> {
+        self.mint_with(self.gold_bar_account())
+    }
+}
+
+#[test]
+fn vaulted_deposit_mints_and_is_consumed() {
+    let deposit = Deposit::new();
+    let minted = deposit.mint().unwrap();
+    assert!(minted.consumed);
+}
+
+#[test]
+fn consumed_deposit_cannot_back_a_second_mint() {
+    let mut deposit = Deposit::new();
+    deposit.consumed = true;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::DepositAlreadyConsumed))
+    );
+}
+
+#[test]
+fn bar_that_left_the_vault_cannot_back_a_mint() {
+    let mut deposit = Deposit::new();
+    deposit.bar_status = BarStatus::InTransit;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::InvalidBarStatus))
+    );
+}
+
+#[test]
+fn gold_bar_must_be_the_deposited_bar() {
+    let deposit = Deposit::new();
+    let mut other = Deposit::new();
+    other.gold_bar = Pubkey::new_unique();
+    assert_eq!(
+        deposit.mint_with(other.gold_bar_account()).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintAddress.into()))
+    );
+}
+
+#[test]
+fn oversized_deposit_is_not_reported_as_a_supply_cap_breach() {
+    let mut deposit = Deposit::new();
+    deposit.fine_weight_mg = u64::MAX;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::DepositAmountOverflow))
+    );
+}
+
+#[test]
+fn deposit_beyond_attested_reserves_is_rejected() {
+    let mut deposit = Deposit::new();
+    // The existing supply plus this bar needs 1_000 + 12_441 grams
+    deposit.vault_grams = SUPPLY / 10u64.pow(DECIMALS as u32) + 12_440;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::InsufficientReserves))
+    );
+}
+
+#[test]
+fn stale_attestation_blocks_minting() {
+    let mut deposit = Deposit::new();
+    deposit.attested_at = NOW - deposit.config.reserve_staleness_window - 1;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::StaleReserveAttestation))
+    );
+}
+
+#[test]
+fn attestation_at_the_edge_of_the_window_still_backs_a_mint() {
+    let mut deposit = Deposit::new();
+    deposit.attested_at = NOW - deposit.config.reserve_staleness_window;
+    assert!(deposit.mint().unwrap().consumed);
+}
+
+#[test]
+fn deposit_beyond_the_supply_cap_is_rejected() {
+    let mut deposit = Deposit::new();
+    deposit.config.supply_cap = SUPPLY + BAR_AMOUNT - 1;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::SupplyCapExceeded))
+    );
+}
+
+#[test]
+fn deposit_up_to_the_supply_cap_mints() {
+    let mut deposit = Deposit::new();
+    deposit.config.supply_cap = SUPPLY + BAR_AMOUNT;
+    assert!(deposit.mint().unwrap().consumed);
+}
+
+#[test]
+fn active_shortfall_blocks_minting() {
+    let mut deposit = Deposit::new();
+    deposit.config.reserve_shortfall_at = NOW - 60;
+    assert_eq!(
+        deposit.mint().err(),
+        Some(gold_token_error(GoldTokenError::ReserveShortfallActive))
+    );
+}
+
+#[test]
+fn mint_into_an_empty_wallet_adds_a_holder() {
+    let deposit = Deposit::new();
+    gatekeeper_calls();
+    deposit.mint().unwrap();
+    assert!(gatekeeper_calls().contains(&gatekeeper_instruction::AddHolder {}.data()));
+}
+
+#[test]
+fn mint_into_a_holding_wallet_adds_no_holder() {
+    let mut deposit = Deposit::new();
+    deposit.recipient_balance = 1;
+    gatekeeper_calls();
+    deposit.mint().unwrap();
+    assert!(!gatekeeper_calls().contains(&gatekeeper_instruction::AddHolder {}.data()));
+}
@@ -0,0 +1,231 @@
+//! Each vault's totals track exactly the registered bars currently `InVault` there.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{
+    account_info, gold_token_error, install_stubs, signer_info, state_account, system_program, NOW,
+};
+use gold_token::{instruction, BarStatus, Config, GoldBar, GoldTokenError, Vault, ID};
+
+const REFINER: &str = "PAMP";
+const SERIAL: &str = "AB12345";
+
+/// Gross weight of a standard 400oz bar
+const GROSS_WEIGHT_MG: u64 = 12_441_000;
+
+/// Fine weight of that bar at 999.9 fineness
+const FINE_WEIGHT_MG: u64 = 12_439_755;
+
+struct Registry {
+    config: AccountInfo<'static>,
+    custodian: Pubkey,
+    vaults: [AccountInfo<'static>; 2],
+    gold_bar: AccountInfo<'static>,
+}
+
+impl Registry {
+    fn new() -> Self {
+        let custodian = Pubkey::new_unique();
+        let config = Config {
+            custodian,
+            ..Config::default()
+        };
+        let vault = |vault_id: u64| {
+            state_account(
+                Pubkey::find_program_address(&[b"vault", &vault_id.to_le_bytes()], &ID).0,
+                &Vault {
+                    vault_id,
+                    location: "London".to_string(),
+                    fine_weight_mg: 0,
+                    bar_count: 0,
+                },
+            )
+        };
+        let key = Pubkey::find_program_address(
+            &[b"gold_bar", REFINER.as_bytes(), SERIAL.as_bytes()],
+            &ID,
+        )
+        .0;
+        let mut gold_bar = account_info(key, System::id(), vec![]);
+        *gold_bar.lamports.borrow_mut() = common::leak(0);
+        gold_bar.is_writable = true;
+        Self {
+            config: state_account(Pubkey::new_unique(), &config),
+            custodian,
+            vaults: [vault(1), vault(2)],
+            gold_bar,
+        }
+    }
+
+    fn run(
+        &self,
+        accounts: Vec<AccountInfo<'static>>,
+        data: Vec<u8>,
+    ) -> std::result::Result<(), ProgramError> {
+        install_stubs();
+        gold_token::entry(&ID, Box::leak(accounts.into_boxed_slice()), &data)
+    }
+
+    fn register_as(
+        &self,
+        custodian: Pubkey,
+        fineness: u16,
+    ) -> std::result::Result<(), ProgramError> {
+        self.run(
+            vec![
+                self.config.clone(),
+                signer_info(custodian),
+                self.vaults[0].clone(),
+                self.gold_bar.clone(),
+                system_program(),
+            ],
+            instruction::RegisterGoldBar {
+                refiner: REFINER.to_string(),
+                serial: SERIAL.to_string(),
+                gross_weight_mg: GROSS_WEIGHT_MG,
+                fineness,
+            }
+            .data(),
+        )
+    }
+
+    fn register(&self) {
+        self.register_as(self.custodian, 9999).unwrap();
+    }
+
+    /// Set the bar's status, tallied against the vault at `vault`
+    fn set_status(
+        &self,
+        vault: usize,
+        new_status: BarStatus,
+    ) -> std::result::Result<(), ProgramError> {
+        self.run(
+            vec![
+                self.config.clone(),
+                signer_info(self.custodian),
+                self.gold_bar.clone(),
+                self.vaults[vault].clone(),
+            ],
+            instruction::UpdateGoldBarStatus { new_status }.data(),
+        )
+    }
+
+    fn move_to(&self, vault: usize) -> std::result::Result<(), ProgramError> {
+        self.run(
+            vec![
+                self.config.clone(),
+                signer_info(self.custodian),
+                self.gold_bar.clone(),
+                self.vaults[vault].clone(),
+            ],
+            instruction::MoveGoldBar {}.data(),
+        )
+    }
+
+    fn gold_bar(&self) -> GoldBar {
+        GoldBar::try_deserialize(&mut &self.gold_bar.try_borrow_data().unwrap()[..]).unwrap()
+    }
+
+    /// Fine weight and bar count held in the vault at `vault`
+    fn totals(&self, vault: usize) -> (u64, u32) {
+        let data = self.vaults[vault].try_borrow_data().unwrap();
+        let vault = Vault::try_deserialize(&mut &data[..]).unwrap();
+        (vault.fine_weight_mg, vault.bar_count)
+    }
+}
+
+#[test]
+fn registered_bar_is_added_to_its_vault() {
+    let registry = Registry::new();
+    registry.register();
+    let bar = registry.gold_bar();
+    assert_eq!(bar.fine_weight_mg, FINE_WEIGHT_MG);
+    assert_eq!(bar.vault, *registry.vaults[0].key);
+    assert!(bar.status == BarStatus::InVault);
+    assert_eq!(bar.registered_at, NOW);
+    assert_eq!(registry.totals(0), (FINE_WEIGHT_MG, 1));
+}
+
+#[test]
+fn fineness_above_the_scale_is_rejected() {
+    let registry = Registry::new();
+    assert_eq!(
+        registry.register_as(registry.custodian, 10_001).err(),
+        Some(gold_token_error(GoldTokenError::InvalidBarDetails))
+    );
+}
+
+#[test]
+fn only_the_custodian_registers_bars() {
+    let registry = Registry::new();
+    assert_eq!(
+        registry.register_as(Pubkey::new_unique(), 9999).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn bar_in_transit_leaves_and_rejoins_the_vault_total() {
+    let registry = Registry::new();
+    registry.register();
+    registry.set_status(0, BarStatus::InTransit).unwrap();
+    assert_eq!(registry.totals(0), (0, 0));
+    registry.set_status(0, BarStatus::InVault).unwrap();
+    assert_eq!(registry.totals(0), (FINE_WEIGHT_MG, 1));
+}
+
+#[test]
+fn moved_bar_joins_the_destination_total_once_vaulted() {
+    let registry = Registry::new();
+    registry.register();
+    registry.set_status(0, BarStatus::InTransit).unwrap();
+    registry.move_to(1).unwrap();
+    assert_eq!(registry.gold_bar().vault, *registry.vaults[1].key);
+    assert_eq!(registry.totals(1), (0, 0));
+    registry.set_status(1, BarStatus::InVault).unwrap();
+    assert_eq!(registry.totals(0), (0, 0));
+    assert_eq!(registry.totals(1), (FINE_WEIGHT_MG, 1));
+}
+
+#[test]
+fn vaulted_bar_cannot_be_moved() {
+    let registry = Registry::new();
+    registry.register();
+    assert_eq!(
+        registry.move_to(1).err(),
+        Some(gold_token_error(GoldTokenError::InvalidBarStatus))
+    );
+}
+
+#[test]
+fn status_is_tallied_against_the_bar_vault_only() {
+    let registry = Registry::new();
+    registry.register();
+    assert_eq!(
+        registry.set_status(1, BarStatus::InTransit).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn redeemed_bar_leaves_the_vault_total_for_good() {
+    let registry = Registry::new();
+    registry.register();
+    registry.set_status(0, BarStatus::Redeemed).unwrap();
+    assert_eq!(registry.totals(0), (0, 0));
+    assert_eq!(
+        registry.set_status(0, BarStatus::InVault).err(),
+        Some(gold_token_error(GoldTokenError::InvalidBarStatus))
+    );
+    assert_eq!(registry.totals(0), (0, 0));
+}
+
+#[test]
+fn bar_redeemed_in_transit_is_not_subtracted_twice() {
+    let registry = Registry::new();
+    registry.register();
+    registry.set_status(0, BarStatus::InTransit).unwrap();
+    registry.set_status(0, BarStatus::Redeemed).unwrap();
+    assert_eq!(registry.totals(0), (0, 0));
+}
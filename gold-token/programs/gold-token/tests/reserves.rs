@@ -0,0 +1,190 @@
+//! The auditor records the latest count of the vaulted gold. A count that no longer backs
+//! the supply flags a shortfall, which only a later attestation covering the supply lets
+//! the admin clear.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{
+    account_info, gold_token_error, install_stubs, mint_account, signer_info, state_account,
+    system_program, DECIMALS, NOW, SUPPLY,
+};
+use gold_token::{instruction, Config, GoldTokenError, ReserveAttestation, ID};
+
+/// Grams that exactly back the test supply
+const BACKING_GRAMS: u64 = SUPPLY / 10u64.pow(DECIMALS as u32);
+
+struct Reserves {
+    config: AccountInfo<'static>,
+    auditor: Pubkey,
+    admin: Pubkey,
+    mint: Pubkey,
+    attestation: AccountInfo<'static>,
+}
+
+impl Reserves {
+    fn new() -> Self {
+        Self::with_shortfall_at(0)
+    }
+
+    /// Reserves whose config already records a shortfall found at `reserve_shortfall_at`
+    /// by its first attestation
+    fn with_shortfall_at(reserve_shortfall_at: i64) -> Self {
+        let attestations = (reserve_shortfall_at != 0) as u64;
+        let auditor = Pubkey::new_unique();
+        let admin = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let config = Config {
+            admin,
+            auditor,
+            mint,
+            reserve_shortfall_at,
+            reserve_attestation_count: attestations,
+            reserve_shortfall_sequence: attestations,
+            ..Config::default()
+        };
+        let key = Pubkey::find_program_address(&[b"reserve_attestation"], &ID).0;
+        let mut attestation = account_info(key, System::id(), vec![]);
+        *attestation.lamports.borrow_mut() = common::leak(0);
+        attestation.is_writable = true;
+        Self {
+            config: state_account(Pubkey::new_unique(), &config),
+            auditor,
+            admin,
+            mint,
+            attestation,
+        }
+    }
+
+    fn config(&self) -> Config {
+        Config::try_deserialize(&mut &self.config.try_borrow_data().unwrap()[..]).unwrap()
+    }
+
+    fn attest_as(
+        &self,
+        auditor: Pubkey,
+        vault_grams: u64,
+    ) -> std::result::Result<(), ProgramError> {
+        install_stubs();
+        let accounts = vec![
+            self.config.clone(),
+            signer_info(auditor),
+            mint_account(self.mint),
+            self.attestation.clone(),
+            system_program(),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::AttestReserves {
+                vault_grams,
+                report_hash: [7; 32],
+            }
+            .data(),
+        )
+    }
+
+    fn attest(&self, vault_grams: u64) -> std::result::Result<(), ProgramError> {
+        self.attest_as(self.auditor, vault_grams)
+    }
+
+    fn acknowledge(&self) -> std::result::Result<(), ProgramError> {
+        install_stubs();
+        let accounts = vec![
+            self.config.clone(),
+            signer_info(self.admin),
+            mint_account(self.mint),
+            self.attestation.clone(),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::AcknowledgeReserveRecovery {}.data(),
+        )
+    }
+}
+
+#[test]
+fn attestation_is_recorded() {
+    let reserves = Reserves::new();
+    reserves.attest(BACKING_GRAMS).unwrap();
+    let data = reserves.attestation.try_borrow_data().unwrap();
+    let attestation = ReserveAttestation::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(attestation.vault_grams, BACKING_GRAMS);
+    assert_eq!(attestation.attested_at, NOW);
+    assert_eq!(attestation.report_hash, [7; 32]);
+    assert_eq!(attestation.auditor, reserves.auditor);
+    assert_eq!(reserves.config().reserve_shortfall_at, 0);
+    assert_eq!(reserves.config().reserve_attestation_count, 1);
+}
+
+#[test]
+fn only_the_auditor_attests() {
+    let reserves = Reserves::new();
+    assert_eq!(
+        reserves
+            .attest_as(Pubkey::new_unique(), BACKING_GRAMS)
+            .err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn under_backed_attestation_flags_a_shortfall() {
+    let reserves = Reserves::new();
+    reserves.attest(BACKING_GRAMS).unwrap();
+    reserves.attest(BACKING_GRAMS - 1).unwrap();
+    let config = reserves.config();
+    assert_eq!(config.reserve_shortfall_at, NOW);
+    assert_eq!(config.reserve_shortfall_sequence, 2);
+}
+
+#[test]
+fn later_healthy_attestation_clears_the_shortfall() {
+    let reserves = Reserves::with_shortfall_at(NOW - 60);
+    reserves.attest(BACKING_GRAMS).unwrap();
+    reserves.acknowledge().unwrap();
+    assert_eq!(reserves.config().reserve_shortfall_at, 0);
+}
+
+#[test]
+fn healthy_attestation_in_the_same_second_clears_the_shortfall() {
+    let reserves = Reserves::new();
+    reserves.attest(BACKING_GRAMS - 1).unwrap();
+    reserves.attest(BACKING_GRAMS).unwrap();
+    reserves.acknowledge().unwrap();
+    assert_eq!(reserves.config().reserve_shortfall_at, 0);
+}
+
+#[test]
+fn shortfall_cannot_be_cleared_by_the_attestation_that_found_it() {
+    let reserves = Reserves::new();
+    reserves.attest(BACKING_GRAMS - 1).unwrap();
+    assert_eq!(
+        reserves.acknowledge().err(),
+        Some(gold_token_error(GoldTokenError::InsufficientReserves))
+    );
+}
+
+#[test]
+fn shortfall_stays_while_later_attestations_fall_short() {
+    let reserves = Reserves::with_shortfall_at(NOW - 60);
+    reserves.attest(BACKING_GRAMS - 1).unwrap();
+    assert_eq!(
+        reserves.acknowledge().err(),
+        Some(gold_token_error(GoldTokenError::InsufficientReserves))
+    );
+    let config = reserves.config();
+    assert_eq!(config.reserve_shortfall_at, NOW - 60);
+    assert_eq!(config.reserve_shortfall_sequence, 1);
+}
+
+#[test]
+fn nothing_to_acknowledge_without_a_shortfall() {
+    let reserves = Reserves::new();
+    reserves.attest(BACKING_GRAMS).unwrap();
+    assert_eq!(
+        reserves.acknowledge().err(),
+        Some(gold_token_error(GoldTokenError::NoReserveShortfall))
+    );
+}
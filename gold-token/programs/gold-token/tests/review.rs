@@ -0,0 +1,242 @@
+//! Large transfers wait in the review escrow until asset protection releases them to
+//! the destination or returns them to the sender.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::StateWithExtensions, state::Account as TokenAccount,
+};
+use common::{
+    account_info, associated_token_program, gold_token_error, install_stubs, mint_account,
+    signer_info, state_account, system_program, token_account, token_program, NOW,
+};
+use gold_token::{instruction, Config, GoldTokenError, ReviewTransfer, ID};
+use transfer_hook_gatekeeper::REVIEW_ESCROW_SEED;
+
+const REVIEW_ID: u64 = 4;
+
+struct Review {
+    config: Config,
+    sender: Pubkey,
+    destination: Pubkey,
+    escrow: Pubkey,
+}
+
+impl Review {
+    fn new() -> Self {
+        Self {
+            config: Config {
+                asset_protection: Pubkey::new_unique(),
+                mint: Pubkey::new_unique(),
+                ..Config::default()
+            },
+            sender: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            escrow: Pubkey::find_program_address(&[REVIEW_ESCROW_SEED], &ID).0,
+        }
+    }
+
+    fn review_key(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                b"review_transfer",
+                self.sender.as_ref(),
+                &REVIEW_ID.to_le_bytes(),
+            ],
+            &ID,
+        )
+        .0
+    }
+
+    /// Escrow `amount` out of a sender holding `balance`, returning the review record and
+    /// the sender's and escrow's token accounts
+    fn request(
+        &self,
+        balance: u64,
+        amount: u64,
+    ) -> std::result::Result<
+        (ReviewTransfer, AccountInfo<'static>, AccountInfo<'static>),
+        ProgramError,
+    > {
+        install_stubs();
+        let mut review = account_info(self.review_key(), System::id(), vec![]);
+        *review.lamports.borrow_mut() = common::leak(0);
+        review.is_writable = true;
+        let sender_token = token_account(self.config.mint, self.sender, balance);
+        let escrow_token = token_account(self.config.mint, self.escrow, 0);
+        let accounts = vec![
+            signer_info(self.sender),
+            state_account(Pubkey::new_unique(), &self.config),
+            mint_account(self.config.mint),
+            sender_token.clone(),
+            account_info(self.destination, System::id(), vec![]),
+            review.clone(),
+            account_info(self.escrow, System::id(), vec![]),
+            escrow_token.clone(),
+            token_program(),
+            associated_token_program(),
+            system_program(),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::TransferViaReview {
+                review_id: REVIEW_ID,
+                amount,
+            }
+            .data(),
+        )?;
+        let record = ReviewTransfer::try_deserialize(&mut &review.try_borrow_data()?[..])?;
+        Ok((record, sender_token, escrow_token))
+    }
+
+    /// Approve or reject an escrowed `amount` into `recipient`'s token account, returning
+    /// the review account and the escrow's and recipient's token accounts
+    fn resolve(
+        &self,
+        approve: bool,
+        authority: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> std::result::Result<[AccountInfo<'static>; 3], ProgramError> {
+        install_stubs();
+        let review = state_account(
+            self.review_key(),
+            &ReviewTransfer {
+                review_id: REVIEW_ID,
+                sender: self.sender,
+                destination: self.destination,
+                amount,
+                requested_at: NOW - 60,
+            },
+        );
+        let escrow_token = token_account(self.config.mint, self.escrow, amount);
+        let recipient_token = token_account(self.config.mint, recipient, 0);
+        let mut sender = account_info(self.sender, System::id(), vec![]);
+        sender.is_writable = true;
+        let accounts = vec![
+            state_account(Pubkey::new_unique(), &self.config),
+            signer_info(authority),
+            mint_account(self.config.mint),
+            sender,
+            review.clone(),
+            account_info(self.escrow, System::id(), vec![]),
+            escrow_token.clone(),
+            recipient_token.clone(),
+            token_program(),
+        ];
+        let data = if approve {
+            instruction::ApproveReviewTransfer {}.data()
+        } else {
+            instruction::RejectReviewTransfer {}.data()
+        };
+        gold_token::entry(&ID, Box::leak(accounts.into_boxed_slice()), &data)?;
+        Ok([review, escrow_token, recipient_token])
+    }
+}
+
+fn balance(token: &AccountInfo) -> u64 {
+    let data = token.try_borrow_data().unwrap();
+    StateWithExtensions::<TokenAccount>::unpack(&data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[test]
+fn request_moves_the_amount_into_escrow() {
+    let review = Review::new();
+    let (record, sender_token, escrow_token) = review.request(1_000, 600).unwrap();
+    assert_eq!(record.review_id, REVIEW_ID);
+    assert_eq!(record.sender, review.sender);
+    assert_eq!(record.destination, review.destination);
+    assert_eq!(record.amount, 600);
+    assert_eq!(record.requested_at, NOW);
+    assert_eq!(balance(&sender_token), 400);
+    assert_eq!(balance(&escrow_token), 600);
+}
+
+#[test]
+fn request_cannot_exceed_the_sender_balance() {
+    let review = Review::new();
+    assert_eq!(
+        review.request(500, 600).err(),
+        Some(gold_token_error(GoldTokenError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn request_is_refused_while_paused() {
+    let mut review = Review::new();
+    review.config.is_paused = true;
+    assert_eq!(
+        review.request(1_000, 600).err(),
+        Some(gold_token_error(GoldTokenError::ContractPaused))
+    );
+}
+
+#[test]
+fn approval_releases_to_the_destination() {
+    let review = Review::new();
+    let [record, escrow_token, recipient_token] = review
+        .resolve(
+            true,
+            review.config.asset_protection,
+            review.destination,
+            600,
+        )
+        .unwrap();
+    assert_eq!(balance(&escrow_token), 0);
+    assert_eq!(balance(&recipient_token), 600);
+    assert_eq!(record.lamports(), 0);
+}
+
+#[test]
+fn approval_cannot_pay_anyone_but_the_destination() {
+    let review = Review::new();
+    assert_eq!(
+        review
+            .resolve(true, review.config.asset_protection, review.sender, 600)
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidReviewRecipient))
+    );
+}
+
+#[test]
+fn rejection_returns_to_the_sender() {
+    let review = Review::new();
+    let [record, escrow_token, recipient_token] = review
+        .resolve(false, review.config.asset_protection, review.sender, 600)
+        .unwrap();
+    assert_eq!(balance(&escrow_token), 0);
+    assert_eq!(balance(&recipient_token), 600);
+    assert_eq!(record.lamports(), 0);
+}
+
+#[test]
+fn rejection_cannot_pay_the_destination() {
+    let review = Review::new();
+    assert_eq!(
+        review
+            .resolve(
+                false,
+                review.config.asset_protection,
+                review.destination,
+                600,
+            )
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidReviewRecipient))
+    );
+}
+
+#[test]
+fn only_asset_protection_resolves_reviews() {
+    let review = Review::new();
+    assert_eq!(
+        review
+            .resolve(true, Pubkey::new_unique(), review.destination, 600)
+            .err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
@@ -0,0 +1,269 @@
+//! Relayed reserve reports are only recorded when the preceding Ed25519 instruction
+//! verified exactly that report under a registered auditor key.
+
+mod common;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{ed25519_program, sysvar::instructions},
+    InstructionData,
+};
+use common::{
+    account_info, gold_token_error, install_stubs, mint_account, signer_info, state_account,
+    system_program, NOW,
+};
+use gold_token::{
+    instruction, AuditorRegistry, Config, GoldTokenError, ReserveAttestation, ReserveReport, ID,
+};
+
+/// Offsets in an Ed25519 instruction that carries a single signature
+const PUBLIC_KEY_OFFSET: u16 = 16;
+const SIGNATURE_OFFSET: u16 = PUBLIC_KEY_OFFSET + 32;
+const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+/// Ed25519 instruction verifying `message` under `signer`, with every offset pointing
+/// at its own data unless `message_instruction_index` says otherwise
+fn ed25519_data(signer: &Pubkey, message: &[u8], message_instruction_index: u16) -> Vec<u8> {
+    let mut data = vec![1, 0];
+    for offset in [
+        SIGNATURE_OFFSET,
+        u16::MAX,
+        PUBLIC_KEY_OFFSET,
+        u16::MAX,
+        MESSAGE_OFFSET,
+        message.len() as u16,
+        message_instruction_index,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    // The runtime checks the signature before the program runs
+    data.extend_from_slice(&[0; 64]);
+    data.extend_from_slice(message);
+    data
+}
+
+/// Instructions sysvar for a transaction of account-less `instructions`, executing the last
+fn instructions_sysvar(instructions: &[(Pubkey, Vec<u8>)]) -> AccountInfo<'static> {
+    let mut data = (instructions.len() as u16).to_le_bytes().to_vec();
+    data.resize(2 + 2 * instructions.len(), 0);
+    for (i, (program_id, ix_data)) in instructions.iter().enumerate() {
+        let start = data.len() as u16;
+        data[2 + 2 * i..4 + 2 * i].copy_from_slice(&start.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data.extend_from_slice(program_id.as_ref());
+        data.extend_from_slice(&(ix_data.len() as u16).to_le_bytes());
+        data.extend_from_slice(ix_data);
+    }
+    data.extend_from_slice(&(instructions.len() as u16 - 1).to_le_bytes());
+    account_info(instructions::ID, System::id(), data)
+}
+
+struct Submission {
+    config: Config,
+    auditor: Pubkey,
+    attestation: AccountInfo<'static>,
+}
+
+impl Submission {
+    fn new() -> Self {
+        let key = Pubkey::find_program_address(&[b"reserve_attestation"], &ID).0;
+        let mut attestation = account_info(key, System::id(), vec![]);
+        *attestation.lamports.borrow_mut() = common::leak(0);
+        attestation.is_writable = true;
+        Self {
+            config: Config {
+                mint: Pubkey::new_unique(),
+                ..Config::default()
+            },
+            auditor: Pubkey::new_unique(),
+            attestation,
+        }
+    }
+
+    fn report(&self, vault_grams: u64) -> ReserveReport {
+        ReserveReport {
+            mint: self.config.mint,
+            vault_grams,
+            report_hash: [9; 32],
+            reported_at: NOW - 60,
+        }
+    }
+
+    /// Relay `report` behind `instructions`, returning the stored attestation
+    fn submit_with(
+        &self,
+        report: ReserveReport,
+        instructions: &[(Pubkey, Vec<u8>)],
+    ) -> std::result::Result<ReserveAttestation, ProgramError> {
+        install_stubs();
+        let registry = AuditorRegistry {
+            auditors: vec![self.auditor],
+        };
+        let accounts = vec![
+            state_account(Pubkey::new_unique(), &self.config),
+            signer_info(Pubkey::new_unique()),
+            mint_account(self.config.mint),
+            state_account(
+                Pubkey::find_program_address(&[b"auditor_registry"], &ID).0,
+                &registry,
+            ),
+            self.attestation.clone(),
+            instructions_sysvar(instructions),
+            system_program(),
+        ];
+        gold_token::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::SubmitSignedAttestation { report }.data(),
+        )?;
+        let data = self.attestation.try_borrow_data()?;
+        Ok(ReserveAttestation::try_deserialize(&mut &data[..]).unwrap())
+    }
+
+    /// Relay `report` right after an Ed25519 verification of it by `signer`
+    fn submit(
+        &self,
+        report: ReserveReport,
+        signer: &Pubkey,
+    ) -> std::result::Result<ReserveAttestation, ProgramError> {
+        let message = report.try_to_vec().unwrap();
+        let ed25519 = (
+            ed25519_program::ID,
+            ed25519_data(signer, &message, u16::MAX),
+        );
+        self.submit_with(report, &[ed25519, (ID, vec![])])
+    }
+}
+
+#[test]
+fn verified_report_is_recorded() {
+    let submission = Submission::new();
+    let attestation = submission
+        .submit(submission.report(1_000), &submission.auditor)
+        .unwrap();
+    assert_eq!(attestation.vault_grams, 1_000);
+    assert_eq!(attestation.attested_at, NOW - 60);
+    assert_eq!(attestation.report_hash, [9; 32]);
+    assert_eq!(attestation.auditor, submission.auditor);
+}
+
+#[test]
+fn report_must_follow_an_instruction() {
+    let submission = Submission::new();
+    assert_eq!(
+        submission
+            .submit_with(submission.report(1_000), &[(ID, vec![])])
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidEd25519Instruction))
+    );
+}
+
+#[test]
+fn verification_by_another_program_is_rejected() {
+    let submission = Submission::new();
+    let report = submission.report(1_000);
+    let message = report.try_to_vec().unwrap();
+    let other = (
+        Pubkey::new_unique(),
+        ed25519_data(&submission.auditor, &message, u16::MAX),
+    );
+    assert_eq!(
+        submission.submit_with(report, &[other, (ID, vec![])]).err(),
+        Some(gold_token_error(GoldTokenError::InvalidEd25519Instruction))
+    );
+}
+
+#[test]
+fn message_in_another_instruction_is_rejected() {
+    let submission = Submission::new();
+    let report = submission.report(1_000);
+    let message = report.try_to_vec().unwrap();
+    // The verified message would be read from the first instruction, not the Ed25519 one
+    let ed25519 = (
+        ed25519_program::ID,
+        ed25519_data(&submission.auditor, &message, 0),
+    );
+    assert_eq!(
+        submission
+            .submit_with(report, &[(ID, vec![]), ed25519, (ID, vec![])])
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidEd25519Instruction))
+    );
+}
+
+#[test]
+fn message_beyond_the_instruction_data_is_rejected() {
+    let submission = Submission::new();
+    let report = submission.report(1_000);
+    let mut data = ed25519_data(&submission.auditor, &report.try_to_vec().unwrap(), u16::MAX);
+    data.truncate(data.len() - 1);
+    assert_eq!(
+        submission
+            .submit_with(report, &[(ed25519_program::ID, data), (ID, vec![])])
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidEd25519Instruction))
+    );
+}
+
+#[test]
+fn verified_message_must_be_the_report() {
+    let submission = Submission::new();
+    let signed = submission.report(1_000).try_to_vec().unwrap();
+    let ed25519 = (
+        ed25519_program::ID,
+        ed25519_data(&submission.auditor, &signed, u16::MAX),
+    );
+    assert_eq!(
+        submission
+            .submit_with(submission.report(2_000), &[ed25519, (ID, vec![])])
+            .err(),
+        Some(gold_token_error(GoldTokenError::InvalidEd25519Instruction))
+    );
+}
+
+#[test]
+fn unregistered_key_is_rejected() {
+    let submission = Submission::new();
+    assert_eq!(
+        submission
+            .submit(submission.report(1_000), &Pubkey::new_unique())
+            .err(),
+        Some(gold_token_error(GoldTokenError::UnknownAuditorKey))
+    );
+}
+
+#[test]
+fn report_cannot_be_replayed() {
+    let submission = Submission::new();
+    let report = submission.report(1_000);
+    submission
+        .submit(report.clone(), &submission.auditor)
+        .unwrap();
+    assert_eq!(
+        submission.submit(report, &submission.auditor).err(),
+        Some(gold_token_error(GoldTokenError::InvalidReserveReport))
+    );
+}
+
+#[test]
+fn report_from_the_future_is_rejected() {
+    let submission = Submission::new();
+    let mut report = submission.report(1_000);
+    report.reported_at = NOW + 1;
+    assert_eq!(
+        submission.submit(report, &submission.auditor).err(),
+        Some(gold_token_error(GoldTokenError::InvalidReserveReport))
+    );
+}
+
+#[test]
+fn report_for_another_mint_is_rejected() {
+    let submission = Submission::new();
+    let mut report = submission.report(1_000);
+    report.mint = Pubkey::new_unique();
+    assert_eq!(
+        submission.submit(report, &submission.auditor).err(),
+        Some(gold_token_error(GoldTokenError::InvalidReserveReport))
+    );
+}
@@ -1,12 +1,27 @@
 use anchor_lang::{
     prelude::*,
-    system_program::{create_account, CreateAccount},
+    system_program::{
+        allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+    },
+    Discriminator,
+};
+use anchor_spl::token_interface::{
+    get_mint_extension_data,
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig,
+            transfer_hook::{TransferHook as TransferHookExtension, TransferHookAccount},
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Account as TokenAccountState,
+    },
+    Mint, TokenAccount,
 };
-use anchor_spl::token_interface::{Mint, TokenAccount};
 use spl_tlv_account_resolution::{
-    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+    account::ExtraAccountMeta, pubkey_data::PubkeyData, seeds::Seed, state::ExtraAccountMetaList,
 };
 use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
+use spl_type_length_value::state::TlvStateBorrowed;
 
 declare_id!("CsMsG5FueDqKdmZ1THbBRhvN2NXkDVsaHCABDsfmL4Ld");
 
@@ -18,26 +33,10 @@ pub mod transfer_hook_gatekeeper {
     pub fn initialize_extra_account_meta_list(
         ctx: Context<InitializeExtraAccountMetaList>,
     ) -> Result<()> {
-        let account_metas = vec![
-            // index 5: source blacklist PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal { bytes: "blacklist".as_bytes().to_vec() },
-                    Seed::AccountKey { index: 3 }, // source token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-            // index 6: destination blacklist PDA
-            ExtraAccountMeta::new_with_seeds(
-                &[
-                    Seed::Literal { bytes: "blacklist".as_bytes().to_vec() },
-                    Seed::AccountKey { index: 2 }, // destination token account owner
-                ],
-                false, // is_signer
-                false, // is_writable
-            )?,
-        ];
+        let account_metas = extra_account_metas(
+            &ctx.accounts.pause_config.key(),
+            &ctx.accounts.config.policies,
+        )?;
 
         let account_size = ExtraAccountMetaList::size_of(account_metas.len())? as u64;
         let lamports = Rent::get()?.minimum_balance(account_size as usize);
@@ -71,11 +70,84 @@ pub mod transfer_hook_gatekeeper {
         Ok(())
     }
 
+    /// Rebuild an existing extra account meta list from the current config, so the
+    /// accounts the hook requires can change without redeploying the mint
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        let account_metas = extra_account_metas(
+            &ctx.accounts.pause_config.key(),
+            &ctx.accounts.config.policies,
+        )?;
+        rewrite_extra_account_metas(
+            &ctx.accounts.extra_account_meta_list,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &account_metas,
+        )
+    }
+
     /// Initialize the gatekeeper configuration
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        ctx.accounts.config.authority = ctx.accounts.authority.key();
+    pub fn initialize(ctx: Context<Initialize>, controller_program: Pubkey) -> Result<()> {
+        require_transfer_hook_authority(
+            &ctx.accounts.mint.to_account_info(),
+            &ctx.accounts.authority.key(),
+        )?;
+
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.authority.key();
+        config.controller_program = controller_program;
+        config.review_escrow = review_escrow(&controller_program);
+        config.compliance_authority = ctx.accounts.authority.key();
+        config.mint = ctx.accounts.mint.key();
+        config.policies = DEFAULT_POLICIES.to_vec();
         Ok(())
     }
+
+    /// Grow a config created by the original program, which held only the authority, to
+    /// the current layout. New fields are zeroed, which decodes to their defaults.
+    /// `controller_program` is recorded too, since only its PDA can replace it later;
+    /// it is ignored for a config already in the current layout.
+    pub fn resize_config(ctx: Context<ResizeConfig>, controller_program: Pubkey) -> Result<()> {
+        let config = &ctx.accounts.config;
+        {
+            let data = config.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == *Config::DISCRIMINATOR,
+                GatekeeperError::InvalidConfig
+            );
+            require_keys_eq!(
+                Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+                ctx.accounts.authority.key(),
+                GatekeeperError::Unauthorized
+            );
+            if data.len() == Config::LEN {
+                return Ok(());
+            }
+            require!(data.len() == Config::BASELINE_LEN, GatekeeperError::InvalidConfig);
+        }
+        require_keys_neq!(
+            controller_program,
+            Pubkey::default(),
+            GatekeeperError::InvalidControllerProgram
+        );
+
+        grow_account(
+            config,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            Config::LEN,
+        )?;
+
+        // The mint binding is recorded now; the seeds already prove it. The original
+        // config only ran the blacklist, which the default chain keeps ahead of it.
+        let mut data = config.try_borrow_mut_data()?;
+        let mut state = Config::try_deserialize(&mut &data[..])?;
+        state.compliance_authority = state.authority;
+        state.controller_program = controller_program;
+        state.mint = ctx.accounts.mint.key();
+        state.review_escrow = review_escrow(&controller_program);
+        state.policies = DEFAULT_POLICIES.to_vec();
+        state.try_serialize(&mut &mut data[..])
+    }
     
     /// Set a new authority for the gatekeeper
     pub fn set_authority(ctx: Context<SetAuthority>) -> Result<()> {
@@ -83,9 +155,259 @@ pub mod transfer_hook_gatekeeper {
         Ok(())
     }
     
+    /// Hand co-signing to a new controller program; the current controller must agree
+    pub fn set_controller_program(ctx: Context<SetControllerProgram>, controller_program: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.controller_program = controller_program;
+        config.review_escrow = review_escrow(&controller_program);
+        Ok(())
+    }
+
+    /// Set who manages the KYC allowlist
+    pub fn set_compliance_authority(ctx: Context<SetComplianceAuthority>) -> Result<()> {
+        ctx.accounts.config.compliance_authority = ctx.accounts.new_compliance_authority.key();
+        Ok(())
+    }
+
+    /// Replace the mint's policy chain and regenerate the ExtraAccountMetaList to match,
+    /// so transfers only resolve the accounts of enabled policies. The blacklist is fixed
+    /// ahead of the chain and is not a `Rule`.
+    pub fn set_policies(ctx: Context<SetPolicies>, policies: Vec<Rule>) -> Result<()> {
+        require!(policies.len() <= MAX_POLICIES, GatekeeperError::InvalidPolicyChain);
+        for (i, rule) in policies.iter().enumerate() {
+            require!(!policies[..i].contains(rule), GatekeeperError::InvalidPolicyChain);
+        }
+
+        let meta_list = &ctx.accounts.extra_account_meta_list;
+        let pause_config = pinned_pause_config(meta_list, &ctx.accounts.config.controller_program)?;
+        rewrite_extra_account_metas(
+            meta_list,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &extra_account_metas(&pause_config, &policies)?,
+        )?;
+
+        ctx.accounts.config.policies = policies;
+        Ok(())
+    }
+
+    /// Set the minimum KYC tier for recipients and the maximum balance each tier may hold
+    pub fn set_kyc_requirements(
+        ctx: Context<SetKycRequirements>,
+        min_kyc_tier: u8,
+        kyc_tier_max_balance: [u64; KYC_TIER_COUNT],
+    ) -> Result<()> {
+        require!((min_kyc_tier as usize) < KYC_TIER_COUNT, GatekeeperError::InvalidKycTier);
+        let config = &mut ctx.accounts.config;
+        config.min_kyc_tier = min_kyc_tier;
+        config.kyc_tier_max_balance = kyc_tier_max_balance;
+        Ok(())
+    }
+
+    /// Set how much each KYC tier may send per velocity window, 0 for no limit
+    pub fn set_velocity_limits(
+        ctx: Context<SetVelocityLimits>,
+        velocity_limit: [u64; KYC_TIER_COUNT],
+    ) -> Result<()> {
+        ctx.accounts.config.velocity_limit = velocity_limit;
+        Ok(())
+    }
+
+    /// Cap each holder's balance and the number of holders, 0 for no limit
+    pub fn set_holding_limits(
+        ctx: Context<SetHoldingLimits>,
+        max_balance: u64,
+        max_holders: u64,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.max_balance = max_balance;
+        config.max_holders = max_holders;
+        Ok(())
+    }
+
+    /// Reconcile the holder count with an off-chain snapshot, such as after the chain
+    /// starts tracking holders
+    pub fn set_holder_count(ctx: Context<SetHoldingLimits>, holder_count: u64) -> Result<()> {
+        ctx.accounts.config.holder_count = holder_count;
+        Ok(())
+    }
+
+    /// Set how long newly minted tokens stay locked, in seconds
+    pub fn set_lockup_period(ctx: Context<SetLockupPeriod>, lockup_period: i64) -> Result<()> {
+        require!(lockup_period >= 0, GatekeeperError::InvalidLockupPeriod);
+        ctx.accounts.config.lockup_period = lockup_period;
+        Ok(())
+    }
+
+    /// Lock `amount` freshly minted tokens in `wallet` for the configured period.
+    /// Each mint is locked as its own tranche, so earlier tranches keep their unlock time.
+    pub fn lock_tokens(ctx: Context<LockTokens>, amount: u64) -> Result<()> {
+        let lockup_period = ctx.accounts.config.lockup_period;
+        if lockup_period == 0 || amount == 0 {
+            return Ok(());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let lockup_info = &ctx.accounts.lockup;
+        let mut lockup = if lockup_info.data_len() == 0 {
+            let mint_key = ctx.accounts.mint.key();
+            let wallet_key = ctx.accounts.wallet.key();
+            create_pda_account(
+                lockup_info,
+                &ctx.accounts.payer,
+                &ctx.accounts.system_program,
+                Lockup::LEN,
+                &[
+                    b"lockup".as_ref(),
+                    mint_key.as_ref(),
+                    wallet_key.as_ref(),
+                    &[ctx.bumps.lockup],
+                ],
+            )?;
+            Lockup::default()
+        } else {
+            require_keys_eq!(*lockup_info.owner, crate::ID, GatekeeperError::InvalidLockup);
+            Lockup::try_deserialize(&mut &lockup_info.try_borrow_data()?[..])?
+        };
+
+        lockup.lock(now, amount, now.saturating_add(lockup_period));
+        lockup.try_serialize(&mut &mut lockup_info.try_borrow_mut_data()?[..])
+    }
+
+    /// Release a wallet's lockup before its unlock time
+    pub fn release_lockup(_ctx: Context<ReleaseLockup>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Count a wallet the controller program minted to from an empty balance.
+    /// Mints do not run the hook, so the controller reports them instead.
+    pub fn add_holder(ctx: Context<UpdateHolderCount>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.policies.contains(&Rule::HolderLimit) {
+            config.holder_count = config.holder_count.saturating_add(1);
+        }
+        Ok(())
+    }
+
+    /// Stop counting a wallet the controller program burned down to zero
+    pub fn remove_holder(ctx: Context<UpdateHolderCount>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        if config.policies.contains(&Rule::HolderLimit) {
+            config.holder_count = config.holder_count.saturating_sub(1);
+        }
+        Ok(())
+    }
+
+    /// Enforce, monitor or switch off a single rule
+    pub fn set_rule_mode(ctx: Context<SetRuleMode>, rule: Rule, mode: RuleMode) -> Result<()> {
+        ctx.accounts.config.rule_modes[rule as usize] = mode;
+        Ok(())
+    }
+
+    /// Set the amount above which transfers need a travel-rule record, 0 for none
+    pub fn set_travel_rule_threshold(
+        ctx: Context<SetTravelRuleThreshold>,
+        travel_rule_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts.config.travel_rule_threshold = travel_rule_threshold;
+        Ok(())
+    }
+
+    /// Set the VASP role that registers travel-rule records
+    pub fn set_vasp_authority(ctx: Context<SetVaspAuthority>) -> Result<()> {
+        ctx.accounts.config.vasp_authority = ctx.accounts.new_vasp_authority.key();
+        Ok(())
+    }
+
+    /// Register the originator and beneficiary information exchanged for an
+    /// above-threshold transfer between two wallets. `nonce` must exceed the pair's last one;
+    /// earlier records stay pending until a transfer consumes them.
+    pub fn register_travel_rule_record(
+        ctx: Context<RegisterTravelRuleRecord>,
+        nonce: u64,
+        amount: u64,
+        payload_hash: [u8; 32],
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.travel_rule_record;
+        require!(nonce > record.nonce, GatekeeperError::InvalidTravelRuleNonce);
+        let entry = TravelRuleEntry {
+            nonce,
+            amount,
+            payload_hash,
+            registered_at: Clock::get()?.unix_timestamp,
+        };
+        let slot = record
+            .pending
+            .iter_mut()
+            .find(|pending| pending.nonce == 0)
+            .ok_or(GatekeeperError::TravelRuleRecordsFull)?;
+        *slot = entry;
+        record.nonce = nonce;
+        record.amount = amount;
+        record.payload_hash = payload_hash;
+        record.registered_at = entry.registered_at;
+        record.consumed = false;
+        Ok(())
+    }
+
+    /// Withdraw a pending travel-rule record whose transfer will not happen, freeing its slot
+    pub fn cancel_travel_rule_record(ctx: Context<CancelTravelRuleRecord>, nonce: u64) -> Result<()> {
+        ctx.accounts
+            .travel_rule_record
+            .cancel(nonce)
+            .ok_or(GatekeeperError::TravelRuleRecordNotPending)?;
+        Ok(())
+    }
+
+    /// Let the mint flow into accounts owned by a vetted pool or lending program, or
+    /// into a single program-derived address such as an escrow. A vault authority that
+    /// holds no account of its own is owned by the system program, so each such vault is
+    /// approved by its address even when its program is approved.
+    pub fn approve_program(ctx: Context<ApproveProgram>, program_id: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.program_registry;
+        if !registry.programs.contains(&program_id) {
+            require!(
+                registry.programs.len() < MAX_APPROVED_PROGRAMS,
+                GatekeeperError::ProgramRegistryFull
+            );
+            registry.programs.push(program_id);
+        }
+        Ok(())
+    }
+
+    /// Stop transfers into accounts owned by a previously approved program
+    pub fn revoke_program(ctx: Context<RevokeProgram>, program_id: Pubkey) -> Result<()> {
+        ctx.accounts.program_registry.programs.retain(|approved| approved != &program_id);
+        Ok(())
+    }
+
+    /// Exempt a treasury or exchange wallet from velocity limits
+    pub fn add_velocity_exemption(_ctx: Context<AddVelocityExemption>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Subject a wallet to velocity limits again
+    pub fn remove_velocity_exemption(_ctx: Context<RemoveVelocityExemption>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Create the outbound tracker a wallet needs before sending under a velocity limit.
+    /// Anyone may pay for it.
+    pub fn open_velocity_state(_ctx: Context<OpenVelocityState>) -> Result<()> {
+        Ok(())
+    }
+
     /// Add an address to the blacklist
-    pub fn add_to_blacklist(_ctx: Context<AddToBlacklist>) -> Result<()> { 
-        Ok(()) 
+    pub fn add_to_blacklist(
+        ctx: Context<AddToBlacklist>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts.blacklist_entry.record(
+            reason_code,
+            case_reference,
+            ctx.accounts.authority.key(),
+        )
     }
     
     /// Remove an address from the blacklist
@@ -93,8 +415,157 @@ pub mod transfer_hook_gatekeeper {
         Ok(()) 
     }
 
+    /// Blacklist every `[target, entry]` pair passed in the remaining accounts
+    pub fn add_to_blacklist_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AddToBlacklistBatch<'info>>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        let authority = ctx.accounts.authority.key();
+        for (target, entry_info, bump) in blacklist_batch(&mint, ctx.remaining_accounts)? {
+            require!(entry_info.data_is_empty(), GatekeeperError::AlreadyBlacklisted);
+            create_pda_account(
+                entry_info,
+                &ctx.accounts.authority,
+                &ctx.accounts.system_program,
+                BlacklistEntry::LEN,
+                &[b"blacklist", mint.as_ref(), target.key.as_ref(), &[bump]],
+            )?;
+
+            let mut entry = BlacklistEntry::default();
+            entry.record(reason_code, case_reference, authority)?;
+            entry.try_serialize(&mut &mut entry_info.try_borrow_mut_data()?[..])?;
+        }
+        Ok(())
+    }
+
+    /// Close the blacklist entry of every `[target, entry]` pair passed in the remaining accounts
+    pub fn remove_from_blacklist_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RemoveFromBlacklistBatch<'info>>,
+    ) -> Result<()> {
+        let mint = ctx.accounts.mint.key();
+        for (_, entry_info, _) in blacklist_batch(&mint, ctx.remaining_accounts)? {
+            require!(
+                entry_info.owner == &crate::ID && !entry_info.data_is_empty(),
+                GatekeeperError::NotBlacklisted
+            );
+            close_account(entry_info, &ctx.accounts.authority)?;
+        }
+        Ok(())
+    }
+
+    /// Copy a legacy global blacklist entry to the per-mint PDA. The legacy entry is
+    /// shared by every mint, so it stays until `close_legacy_blacklist_entry`. The hook
+    /// never reads legacy entries, so the controller only migrates while it is paused.
+    pub fn migrate_blacklist_entry(
+        ctx: Context<MigrateBlacklistEntry>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.legacy_blacklist_entry.try_borrow_data()?[..] == *BlacklistEntry::DISCRIMINATOR,
+            GatekeeperError::InvalidBlacklistEntry
+        );
+
+        ctx.accounts.blacklist_entry.record(
+            reason_code,
+            case_reference,
+            ctx.accounts.authority.key(),
+        )
+    }
+
+    /// Close a legacy global blacklist entry once every mint has migrated it; this
+    /// mint's own entry must already exist so closing never lifts its blacklisting
+    pub fn close_legacy_blacklist_entry(ctx: Context<CloseLegacyBlacklistEntry>) -> Result<()> {
+        require!(
+            ctx.accounts.legacy_blacklist_entry.try_borrow_data()?[..] == *BlacklistEntry::DISCRIMINATOR,
+            GatekeeperError::InvalidBlacklistEntry
+        );
+        close_account(
+            &ctx.accounts.legacy_blacklist_entry,
+            &ctx.accounts.authority,
+        )
+    }
+
+    /// Grow a per-mint entry created before entries carried case details
+    pub fn resize_blacklist_entry(
+        ctx: Context<ResizeBlacklistEntry>,
+        reason_code: u16,
+        case_reference: [u8; 32],
+    ) -> Result<()> {
+        let entry_info = &ctx.accounts.blacklist_entry;
+        require!(
+            entry_info.try_borrow_data()?[..] == *BlacklistEntry::DISCRIMINATOR,
+            GatekeeperError::InvalidBlacklistEntry
+        );
+        grow_account(
+            entry_info,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            BlacklistEntry::LEN,
+        )?;
+
+        let mut entry = BlacklistEntry::default();
+        entry.record(reason_code, case_reference, ctx.accounts.authority.key())?;
+        entry.try_serialize(&mut &mut entry_info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// Mark a wallet as KYC-verified
+    pub fn add_to_allowlist(_ctx: Context<AddToAllowlist>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Revoke a wallet's KYC verification
+    pub fn remove_from_allowlist(_ctx: Context<RemoveFromAllowlist>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Record or refresh a wallet's KYC verification
+    pub fn set_kyc_attestation(
+        ctx: Context<SetKycAttestation>,
+        tier: u8,
+        provider: Pubkey,
+        country_code: [u8; 2],
+        expires_at: i64,
+    ) -> Result<()> {
+        require!((tier as usize) < KYC_TIER_COUNT, GatekeeperError::InvalidKycTier);
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at > now, GatekeeperError::KycExpired);
+
+        let attestation = &mut ctx.accounts.kyc_attestation;
+        attestation.tier = tier;
+        attestation.provider = provider;
+        attestation.country_code = country_code;
+        attestation.issued_at = now;
+        attestation.expires_at = expires_at;
+        Ok(())
+    }
+
+    /// Remove a wallet's KYC verification
+    pub fn revoke_kyc_attestation(_ctx: Context<RevokeKycAttestation>) -> Result<()> {
+        Ok(())
+    }
+
     /// The main transfer hook execution function
-    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+    pub fn transfer_hook<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferHook<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        // Only Token-2022 sets `transferring`, so a standalone call cannot pass as a transfer
+        require_transferring(&ctx.accounts.source_token.to_account_info())?;
+
+        // Halt circulation while the controlling program is paused
+        let pause_config = ctx.accounts.pause_config.try_borrow_data()?;
+        let is_paused = pause_config
+            .get(CONTROLLER_PAUSED_OFFSET)
+            .ok_or(GatekeeperError::InvalidPauseConfig)?;
+        if *is_paused != 0 {
+            msg!("Token is paused. Transfer denied.");
+            return err!(GatekeeperError::TransfersPaused);
+        }
+
         // Check if source blacklist PDA exists (has data)
         if ctx.accounts.source_blacklist_entry.data_len() > 0 {
             msg!("Source address is blacklisted. Transfer denied.");
@@ -107,6 +578,274 @@ pub mod transfer_hook_gatekeeper {
             return err!(GatekeeperError::AddressBlacklisted);
         }
 
+        // A blacklisted delegate cannot move tokens on anyone's behalf
+        if ctx.accounts.authority_blacklist_entry.data_len() > 0 {
+            msg!("Transfer authority is blacklisted. Transfer denied.");
+            return err!(GatekeeperError::AddressBlacklisted);
+        }
+
+        // Run the mint's policy chain; each policy consumes its own extra accounts in order
+        let accounts = &ctx.accounts;
+        let mint = accounts.mint.key();
+        let source_owner = accounts.source_token.owner;
+        let destination_owner = accounts.destination_token.owner;
+        let mut extra = ctx.remaining_accounts.iter();
+        let mut holder_count = None;
+        for &rule in &accounts.config.policies {
+            match rule {
+                // Both parties must be KYC-verified
+                Rule::Allowlist => {
+                    let source =
+                        next_policy_account(&mut extra, b"allowlist", &mint, &source_owner)?;
+                    let destination =
+                        next_policy_account(&mut extra, b"allowlist", &mint, &destination_owner)?;
+                    if !accounts.config.enabled(rule) {
+                        continue;
+                    }
+                    if source.data_len() == 0 {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::AddressNotAllowlisted,
+                            "Source address is not allowlisted.",
+                            amount,
+                        )?;
+                    }
+                    if destination.data_len() == 0 {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::AddressNotAllowlisted,
+                            "Destination address is not allowlisted.",
+                            amount,
+                        )?;
+                    }
+                }
+                // The recipient must hold a current attestation of sufficient tier
+                Rule::Kyc => {
+                    let attestation =
+                        next_policy_account(&mut extra, b"kyc", &mint, &destination_owner)?;
+                    if !accounts.config.enabled(rule) {
+                        continue;
+                    }
+                    if let Some((error, reason)) = kyc_violation(
+                        &accounts.config,
+                        attestation,
+                        accounts.destination_token.amount,
+                    )? {
+                        accounts.violation(rule, error, reason, amount)?;
+                    }
+                }
+                // Cap what the sender may move per window unless the wallet is exempt
+                Rule::Velocity => {
+                    let state_info =
+                        next_policy_account(&mut extra, b"velocity", &mint, &source_owner)?;
+                    let exemption =
+                        next_policy_account(&mut extra, b"velocity_exempt", &mint, &source_owner)?;
+                    let attestation =
+                        next_policy_account(&mut extra, b"kyc", &mint, &source_owner)?;
+                    let tier = source_kyc_tier(attestation)?;
+                    let limit = accounts.config.velocity_limit[tier as usize];
+                    if limit == 0 || !accounts.config.enabled(rule) || exemption.data_len() > 0 {
+                        continue;
+                    }
+                    // Transfers into and out of review escrow are vetted by asset protection instead
+                    let review_escrow = accounts.config.review_escrow;
+                    if source_owner == review_escrow || destination_owner == review_escrow {
+                        continue;
+                    }
+                    if state_info.owner != &crate::ID || state_info.data_len() < VelocityState::LEN {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::VelocityStateMissing,
+                            "Source address has no velocity state.",
+                            amount,
+                        )?;
+                        continue;
+                    }
+                    let mut data = state_info.try_borrow_mut_data()?;
+                    let mut state = VelocityState::try_deserialize(&mut &data[..])?;
+                    state.record(Clock::get()?.unix_timestamp, amount);
+                    if state.outbound > limit {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::VelocityLimitExceeded,
+                            "Source address exceeded its velocity limit.",
+                            amount,
+                        )?;
+                    }
+                    state.try_serialize(&mut &mut data[..])?;
+                }
+                // Balances are already updated, so both sides hold their post-transfer amounts
+                Rule::Lockup => {
+                    let lockup =
+                        next_policy_account(&mut extra, b"lockup", &mint, &source_owner)?;
+                    if !accounts.config.enabled(rule)
+                        || lockup.owner != &crate::ID
+                        || lockup.data_len() == 0
+                    {
+                        continue;
+                    }
+                    let locked = Lockup::try_deserialize(&mut &lockup.try_borrow_data()?[..])?
+                        .locked_at(Clock::get()?.unix_timestamp);
+                    if accounts.source_token.amount < locked {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::TokensLocked,
+                            "Source tokens are still locked.",
+                            amount,
+                        )?;
+                    }
+                }
+                Rule::MaxBalance => {
+                    let max_balance = accounts.config.max_balance;
+                    if accounts.config.enabled(rule)
+                        && max_balance > 0
+                        && accounts.destination_token.amount > max_balance
+                    {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::MaxBalanceExceeded,
+                            "Destination balance exceeds the per-holder maximum.",
+                            amount,
+                        )?;
+                    }
+                }
+                // Above the threshold, consume the record the VASP registered for this pair
+                Rule::TravelRule => {
+                    let record_info = extra.next().ok_or(GatekeeperError::InvalidPolicyAccount)?;
+                    let (expected, _) = Pubkey::find_program_address(
+                        &[
+                            b"travel_rule",
+                            mint.as_ref(),
+                            source_owner.as_ref(),
+                            destination_owner.as_ref(),
+                        ],
+                        &crate::ID,
+                    );
+                    require_keys_eq!(
+                        record_info.key(),
+                        expected,
+                        GatekeeperError::InvalidPolicyAccount
+                    );
+                    let threshold = accounts.config.travel_rule_threshold;
+                    if !accounts.config.enabled(rule) || threshold == 0 || amount <= threshold {
+                        continue;
+                    }
+                    let mut record = if record_info.owner == &crate::ID && record_info.data_len() > 0 {
+                        Some(TravelRuleRecord::try_deserialize(
+                            &mut &record_info.try_borrow_data()?[..],
+                        )?)
+                    } else {
+                        None
+                    };
+                    // The oldest pending record for exactly this amount covers the transfer
+                    let consumed = record.as_mut().and_then(|record| record.consume(amount));
+                    match (record, consumed) {
+                        (Some(record), Some(entry)) => {
+                            record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+                            emit!(TravelRuleRecordConsumed {
+                                mint,
+                                source_owner,
+                                destination_owner,
+                                nonce: entry.nonce,
+                                amount,
+                                payload_hash: entry.payload_hash,
+                            });
+                        }
+                        _ => accounts.violation(
+                            rule,
+                            GatekeeperError::TravelRuleRecordMissing,
+                            "Transfer above the travel-rule threshold has no matching record.",
+                            amount,
+                        )?,
+                    }
+                }
+                // Wallets pass; accounts owned by a program must belong to an approved one
+                Rule::ProgramAllowlist => {
+                    let owner_info = extra.next().ok_or(GatekeeperError::InvalidPolicyAccount)?;
+                    require_keys_eq!(
+                        owner_info.key(),
+                        destination_owner,
+                        GatekeeperError::InvalidPolicyAccount
+                    );
+                    let registry_info =
+                        extra.next().ok_or(GatekeeperError::InvalidPolicyAccount)?;
+                    let (registry_key, _) = Pubkey::find_program_address(
+                        &[b"program_registry", mint.as_ref()],
+                        &crate::ID,
+                    );
+                    require_keys_eq!(
+                        registry_info.key(),
+                        registry_key,
+                        GatekeeperError::InvalidPolicyAccount
+                    );
+                    // Only an on-curve, system-owned owner is a wallet; a PDA is program-controlled
+                    // even before it holds an account of its own
+                    let owner_program = owner_info.owner;
+                    let is_wallet =
+                        owner_program == &System::id() && destination_owner.is_on_curve();
+                    // The review escrow belongs to the controller program itself
+                    let is_escrow = destination_owner == accounts.config.review_escrow;
+                    if !accounts.config.enabled(rule) || is_wallet || is_escrow {
+                        continue;
+                    }
+                    // A PDA without an account cannot name its program, so it is approved by
+                    // address, even when the program deriving it is approved
+                    let approved = registry_info.owner == &crate::ID
+                        && registry_info.data_len() > 0
+                        && ProgramRegistry::try_deserialize(
+                            &mut &registry_info.try_borrow_data()?[..],
+                        )
+                        .map(|registry| {
+                            registry.programs.contains(owner_program)
+                                || registry.programs.contains(&destination_owner)
+                        })?;
+                    if !approved {
+                        accounts.violation(
+                            rule,
+                            GatekeeperError::DestinationProgramNotApproved,
+                            "Destination is owned by an unapproved program.",
+                            amount,
+                        )?;
+                    }
+                }
+                // Track wallets entering and leaving the holder set
+                Rule::HolderLimit => {
+                    let self_transfer =
+                        accounts.source_token.key() == accounts.destination_token.key();
+                    if amount == 0 || self_transfer {
+                        continue;
+                    }
+                    let mut count = accounts.config.holder_count;
+                    if accounts.source_token.amount == 0 {
+                        count = count.saturating_sub(1);
+                    }
+                    // Under a transfer fee the destination is only credited the net amount
+                    let credited = credited_amount(&accounts.mint.to_account_info(), amount)?;
+                    if accounts.destination_token.amount == credited {
+                        count = count.saturating_add(1);
+                        let max_holders = accounts.config.max_holders;
+                        if accounts.config.enabled(rule) && max_holders > 0 && count > max_holders {
+                            accounts.violation(
+                                rule,
+                                GatekeeperError::HolderLimitExceeded,
+                                "Transfer would exceed the maximum holder count.",
+                                amount,
+                            )?;
+                        }
+                    }
+                    holder_count = Some(count);
+                }
+            }
+        }
+
+        // The config is only writable when the chain tracks holders
+        if let Some(count) = holder_count {
+            let config = &mut ctx.accounts.config;
+            config.holder_count = count;
+            let info = config.to_account_info();
+            config.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        }
+
         msg!("Transfer approved");
         Ok(())
     }
@@ -128,11 +867,416 @@ pub mod transfer_hook_gatekeeper {
     }
 }
 
+/// Amount the destination receives for `amount`, net of the mint's transfer fee
+fn credited_amount(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let Ok(fee_config) = get_mint_extension_data::<TransferFeeConfig>(mint) else {
+        return Ok(amount);
+    };
+    let fee = fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(amount.saturating_sub(fee))
+}
+
+/// Only the mint's transfer-hook authority may bind a gatekeeper config to it, and only
+/// when the mint's hook actually points at this program
+fn require_transfer_hook_authority(mint: &AccountInfo, authority: &Pubkey) -> Result<()> {
+    let hook = get_mint_extension_data::<TransferHookExtension>(mint)
+        .map_err(|_| error!(GatekeeperError::HookNotConfigured))?;
+    require!(
+        Option::<Pubkey>::from(hook.program_id) == Some(crate::ID),
+        GatekeeperError::HookNotConfigured
+    );
+    require!(
+        Option::<Pubkey>::from(hook.authority) == Some(*authority),
+        GatekeeperError::Unauthorized
+    );
+    Ok(())
+}
+
+/// First rule the recipient's attestation breaks in `KycAttestation` mode, if any
+fn kyc_violation(
+    config: &Config,
+    attestation: &AccountInfo,
+    destination_balance: u64,
+) -> Result<Option<(GatekeeperError, &'static str)>> {
+    if attestation.data_len() == 0 {
+        return Ok(Some((
+            GatekeeperError::KycMissing,
+            "Destination address has no KYC attestation.",
+        )));
+    }
+    let attestation = KycAttestation::try_deserialize(&mut &attestation.try_borrow_data()?[..])?;
+    if attestation.expires_at <= Clock::get()?.unix_timestamp {
+        return Ok(Some((
+            GatekeeperError::KycExpired,
+            "Destination KYC attestation has expired.",
+        )));
+    }
+    if attestation.tier < config.min_kyc_tier {
+        return Ok(Some((
+            GatekeeperError::KycTierTooLow,
+            "Destination KYC tier is below the minimum.",
+        )));
+    }
+    let max_balance = config.kyc_tier_max_balance[attestation.tier as usize];
+    if max_balance > 0 && destination_balance > max_balance {
+        return Ok(Some((
+            GatekeeperError::KycBalanceLimitExceeded,
+            "Destination balance exceeds its KYC tier limit.",
+        )));
+    }
+    Ok(None)
+}
+
+/// Tier of an unexpired attestation, or tier 0 for wallets without one
+fn source_kyc_tier(attestation: &AccountInfo) -> Result<u8> {
+    if attestation.owner != &crate::ID || attestation.data_len() == 0 {
+        return Ok(0);
+    }
+    let attestation = KycAttestation::try_deserialize(&mut &attestation.try_borrow_data()?[..])?;
+    if attestation.expires_at <= Clock::get()?.unix_timestamp {
+        return Ok(0);
+    }
+    Ok(attestation.tier)
+}
+
+/// Token-2022 flags the source account as `transferring` for the duration of the hook CPI
+fn require_transferring(source_token: &AccountInfo) -> Result<()> {
+    let data = source_token.try_borrow_data()?;
+    let account = StateWithExtensions::<TokenAccountState>::unpack(&data)?;
+    let transferring = account
+        .get_extension::<TransferHookAccount>()
+        .map(|extension| bool::from(extension.transferring))
+        .unwrap_or(false);
+    require!(transferring, GatekeeperError::NotTransferring);
+    Ok(())
+}
+
+/// Top up rent from `payer` and zero-extend `account` to `new_len`
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.to_account_info(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+/// Rewrite an ExtraAccountMetaList in place, growing the account first when the new list
+/// does not fit
+fn rewrite_extra_account_metas<'info>(
+    meta_list: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    account_metas: &[ExtraAccountMeta],
+) -> Result<()> {
+    let account_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+    if account_size > meta_list.data_len() {
+        grow_account(meta_list, payer, system_program, account_size)?;
+    }
+    ExtraAccountMetaList::update::<ExecuteInstruction>(
+        &mut meta_list.try_borrow_mut_data()?,
+        account_metas,
+    )?;
+    Ok(())
+}
+
+/// Create a program-owned PDA, tolerating lamports sent to the address beforehand
+fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    let system_program = system_program.to_account_info();
+    if current == 0 {
+        return create_account(
+            CpiContext::new(
+                system_program,
+                CreateAccount {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            )
+            .with_signer(&[signer_seeds]),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+
+    let shortfall = rent.saturating_sub(current);
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    allocate(
+        CpiContext::new(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+        )
+        .with_signer(&[signer_seeds]),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new(
+            system_program,
+            Assign {
+                account_to_assign: account.clone(),
+            },
+        )
+        .with_signer(&[signer_seeds]),
+        &crate::ID,
+    )
+}
+
+/// Split batch remaining accounts into `(target, entry, bump)`, checking each entry is
+/// the target's blacklist PDA for `mint`
+fn blacklist_batch<'a, 'info>(
+    mint: &Pubkey,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<Vec<(&'a AccountInfo<'info>, &'a AccountInfo<'info>, u8)>> {
+    let pairs = accounts.chunks_exact(2);
+    require!(
+        !accounts.is_empty() && pairs.remainder().is_empty(),
+        GatekeeperError::InvalidBatch
+    );
+    pairs
+        .map(|pair| {
+            let (target, entry) = (&pair[0], &pair[1]);
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"blacklist", mint.as_ref(), target.key.as_ref()],
+                &crate::ID,
+            );
+            require_keys_eq!(entry.key(), expected, GatekeeperError::InvalidBatch);
+            Ok((target, entry, bump))
+        })
+        .collect()
+}
+
+/// Close an account that can no longer be deserialized as its current type
+fn close_account<'info>(account: &AccountInfo<'info>, destination: &AccountInfo<'info>) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&System::id());
+    account.resize(0)?;
+    Ok(())
+}
+
+/// Seed of the controller program PDA that signs blacklist changes
+pub const CONTROLLER_AUTHORITY_SEED: &[u8] = b"gatekeeper_controller";
+
+/// Seed of the controller program PDA that holds transfers awaiting compliance review
+pub const REVIEW_ESCROW_SEED: &[u8] = b"review_escrow";
+
+/// Review escrow PDA of `controller_program`, recorded in `Config` so the hook never derives it
+pub fn review_escrow(controller_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REVIEW_ESCROW_SEED], controller_program).0
+}
+
+/// Seed of the controller program's config, whose pause flag the hook reads
+pub const CONTROLLER_CONFIG_SEED: &[u8] = b"config";
+
+/// Config PDA of `controller_program`, the only account a meta list may pin as pause config
+pub fn controller_config(controller_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONTROLLER_CONFIG_SEED], controller_program).0
+}
+
+/// Offset of `is_paused` in gold_token's `Config`: discriminator, six pubkeys, redemption counter
+pub const CONTROLLER_PAUSED_OFFSET: usize = 8 + 32 * 6 + 8;
+
+/// Extra accounts Token-2022 resolves for every `Execute` call on a gatekeeper mint:
+/// the blacklists, pause flag and config, then the accounts of each policy in chain order
+pub fn extra_account_metas(
+    pause_config: &Pubkey,
+    policies: &[Rule],
+) -> Result<Vec<ExtraAccountMeta>> {
+    let mut metas = vec![
+        // index 5: source blacklist PDA
+        source_pda("blacklist", false)?,
+        // index 6: destination blacklist PDA
+        destination_pda("blacklist")?,
+        // index 7: controlling program's config holding the pause flag
+        ExtraAccountMeta::new_with_pubkey(pause_config, false, false)?,
+        // index 8: gatekeeper config, writable when the chain maintains the holder count
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal { bytes: "config".as_bytes().to_vec() },
+                Seed::AccountKey { index: 1 }, // mint
+            ],
+            false, // is_signer
+            policies.contains(&Rule::HolderLimit), // is_writable
+        )?,
+        // index 9: blacklist PDA of the transfer authority, the owner or a delegate
+        ExtraAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal { bytes: "blacklist".as_bytes().to_vec() },
+                Seed::AccountKey { index: 1 }, // mint
+                Seed::AccountKey { index: 3 }, // transfer authority
+            ],
+            false, // is_signer
+            false, // is_writable
+        )?,
+    ];
+    for rule in policies {
+        match rule {
+            Rule::Allowlist => {
+                metas.push(source_pda("allowlist", false)?);
+                metas.push(destination_pda("allowlist")?);
+            }
+            Rule::Kyc => metas.push(destination_pda("kyc")?),
+            Rule::Velocity => {
+                // updated on every limited transfer
+                metas.push(source_pda("velocity", true)?);
+                metas.push(source_pda("velocity_exempt", false)?);
+                // selects the velocity tier
+                metas.push(source_pda("kyc", false)?);
+            }
+            Rule::Lockup => metas.push(source_pda("lockup", false)?),
+            Rule::MaxBalance | Rule::HolderLimit => {}
+            // consumed by the transfer it covers
+            Rule::TravelRule => metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal { bytes: b"travel_rule".to_vec() },
+                    Seed::AccountKey { index: 1 }, // mint
+                    // source token account owner, read from the token account data
+                    Seed::AccountData { account_index: 0, data_index: 32, length: 32 },
+                    // destination token account owner, read from the token account data
+                    Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+                ],
+                false, // is_signer
+                true,  // is_writable
+            )?),
+            Rule::ProgramAllowlist => {
+                // destination token account owner itself, to see which program owns it
+                metas.push(ExtraAccountMeta::new_with_pubkey_data(
+                    &PubkeyData::AccountData { account_index: 2, data_index: 32 },
+                    false, // is_signer
+                    false, // is_writable
+                )?);
+                metas.push(ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal { bytes: b"program_registry".to_vec() },
+                        Seed::AccountKey { index: 1 }, // mint
+                    ],
+                    false, // is_signer
+                    false, // is_writable
+                )?);
+            }
+        }
+    }
+    Ok(metas)
+}
+
+/// `[prefix, mint, source token account owner]` PDA
+fn source_pda(prefix: &str, is_writable: bool) -> Result<ExtraAccountMeta> {
+    Ok(ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal { bytes: prefix.as_bytes().to_vec() },
+            Seed::AccountKey { index: 1 }, // mint
+            // source token account owner, read from the token account data so a
+            // delegate signing the transfer cannot stand in for it
+            Seed::AccountData { account_index: 0, data_index: 32, length: 32 },
+        ],
+        false, // is_signer
+        is_writable,
+    )?)
+}
+
+/// `[prefix, mint, destination token account owner]` PDA
+fn destination_pda(prefix: &str) -> Result<ExtraAccountMeta> {
+    Ok(ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal { bytes: prefix.as_bytes().to_vec() },
+            Seed::AccountKey { index: 1 }, // mint
+            // destination token account owner, read from the token account data
+            Seed::AccountData { account_index: 2, data_index: 32, length: 32 },
+        ],
+        false, // is_signer
+        false, // is_writable
+    )?)
+}
+
+/// Next account of the policy chain, which must be the `[seed, mint, owner]` PDA
+fn next_policy_account<'a, 'info>(
+    accounts: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+    seed: &[u8],
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<&'a AccountInfo<'info>> {
+    let account = accounts.next().ok_or(GatekeeperError::InvalidPolicyAccount)?;
+    let (expected, _) =
+        Pubkey::find_program_address(&[seed, mint.as_ref(), owner.as_ref()], &crate::ID);
+    require_keys_eq!(account.key(), expected, GatekeeperError::InvalidPolicyAccount);
+    Ok(account)
+}
+
+/// Pause config pinned in an existing ExtraAccountMetaList, right after the two blacklist PDAs.
+/// It must be `controller_program`'s config.
+fn pinned_pause_config(meta_list: &AccountInfo, controller_program: &Pubkey) -> Result<Pubkey> {
+    let data = meta_list.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data)?;
+    let metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+    let pause_config = metas
+        .data()
+        .get(2)
+        .filter(|meta| meta.discriminator == 0)
+        .ok_or(GatekeeperError::InvalidPauseConfig)?;
+    let pause_config = Pubkey::new_from_array(pause_config.address_config);
+    require_keys_eq!(
+        pause_config,
+        controller_config(controller_program),
+        GatekeeperError::InvalidPauseConfig
+    );
+    Ok(pause_config)
+}
+
 /// Initialize extra account meta list
 #[derive(Accounts)]
 pub struct InitializeExtraAccountMetaList<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
     /// CHECK: ExtraAccountMetaList Account, must use these seeds
     #[account(
         mut,
@@ -141,6 +1285,14 @@ pub struct InitializeExtraAccountMetaList<'info> {
     )]
     pub extra_account_meta_list: AccountInfo<'info>,
     pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Controlling program's config; its `is_paused` flag gates every transfer
+    #[account(
+        owner = config.controller_program @ GatekeeperError::InvalidPauseConfig,
+        seeds = [CONTROLLER_CONFIG_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub pause_config: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -149,12 +1301,12 @@ pub struct InitializeExtraAccountMetaList<'info> {
 pub struct Initialize<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    /// CHECK: Authority can be any account
+    /// Must be the mint's transfer-hook authority
     pub authority: Signer<'info>,
     #[account(
         init,
         payer = payer,
-        space = 8 + 32, // discriminator + pubkey
+        space = Config::LEN,
         seeds = [b"config", mint.key().as_ref()],
         bump
     )]
@@ -179,47 +1331,516 @@ pub struct SetAuthority<'info> {
     pub mint: InterfaceAccount<'info, Mint>,
 }
 
-/// Add an address to the blacklist
+/// Grow an existing config to the current layout
 #[derive(Accounts)]
-pub struct AddToBlacklist<'info> {
+pub struct ResizeConfig<'info> {
+    /// CHECK: Deserialized by hand, since older layouts are shorter than `Config::LEN`
     #[account(
-        has_one = authority,
+        mut,
+        owner = crate::ID,
         seeds = [b"config", mint.key().as_ref()],
         bump
     )]
-    pub config: Account<'info, Config>,
+    pub config: UncheckedAccount<'info>,
     #[account(mut)]
     pub authority: Signer<'info>,
-    /// CHECK: The address being blacklisted
-    pub target_address: AccountInfo<'info>,
-    #[account(
-        init,
-        payer = authority,
-        space = 8, // Just the discriminator
-        seeds = [b"blacklist", target_address.key().as_ref()],
-        bump
-    )]
-    pub blacklist_entry: Account<'info, BlacklistEntry>,
     pub mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>,
 }
 
-/// Remove an address from the blacklist
+/// Set the controller program
 #[derive(Accounts)]
-pub struct RemoveFromBlacklist<'info> {
+pub struct SetControllerProgram<'info> {
     #[account(
+        mut,
         has_one = authority,
         seeds = [b"config", mint.key().as_ref()],
         bump
     )]
     pub config: Account<'info, Config>,
-    #[account(mut)]
     pub authority: Signer<'info>,
-    /// CHECK: The address being removed from blacklist
-    pub target_address: AccountInfo<'info>,
+    /// PDA of the current controller program, which must sign off on its replacement
     #[account(
-        mut,
-        seeds = [b"blacklist", target_address.key().as_ref()],
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Set a new compliance authority
+#[derive(Accounts)]
+pub struct SetComplianceAuthority<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    /// CHECK: New compliance authority can be any account
+    pub new_compliance_authority: AccountInfo<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Rebuild the extra account meta list
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    /// CHECK: Controlling program's config; its `is_paused` flag gates every transfer
+    #[account(
+        owner = config.controller_program @ GatekeeperError::InvalidPauseConfig,
+        seeds = [CONTROLLER_CONFIG_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub pause_config: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Replace the policy chain
+#[derive(Accounts)]
+pub struct SetPolicies<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: ExtraAccountMetaList Account, rewritten for the new chain
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Change the KYC tier requirements
+#[derive(Accounts)]
+pub struct SetKycRequirements<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Change the per-tier velocity limits
+#[derive(Accounts)]
+pub struct SetVelocityLimits<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Change the holding limits or the holder count
+#[derive(Accounts)]
+pub struct SetHoldingLimits<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Change the lockup period for newly minted tokens
+#[derive(Accounts)]
+pub struct SetLockupPeriod<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Lock newly minted tokens
+#[derive(Accounts)]
+pub struct LockTokens<'info> {
+    #[account(
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The wallet receiving the minted tokens
+    pub wallet: AccountInfo<'info>,
+    /// CHECK: Created on the first lock, so a zero lockup period opens no account
+    #[account(
+        mut,
+        seeds = [b"lockup", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub lockup: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Close a lockup early
+#[derive(Accounts)]
+pub struct ReleaseLockup<'info> {
+    #[account(
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The wallet whose lockup is released
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"lockup", mint.key().as_ref(), wallet.key().as_ref()],
+        bump,
+        close = rent_receiver
+    )]
+    pub lockup: Account<'info, Lockup>,
+    /// CHECK: Receives the lockup's rent
+    #[account(mut)]
+    pub rent_receiver: AccountInfo<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Keep the holder count in step with the controller program's mints and burns
+#[derive(Accounts)]
+pub struct UpdateHolderCount<'info> {
+    #[account(
+        mut,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Change how a rule is applied
+#[derive(Accounts)]
+pub struct SetRuleMode<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Change the travel-rule threshold
+#[derive(Accounts)]
+pub struct SetTravelRuleThreshold<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Set a new VASP authority
+#[derive(Accounts)]
+pub struct SetVaspAuthority<'info> {
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    /// CHECK: New VASP authority can be any account
+    pub new_vasp_authority: AccountInfo<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Register a travel-rule record for a source and destination wallet
+#[derive(Accounts)]
+pub struct RegisterTravelRuleRecord<'info> {
+    #[account(
+        has_one = vasp_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub vasp_authority: Signer<'info>,
+    /// CHECK: The originator wallet
+    pub source: AccountInfo<'info>,
+    /// CHECK: The beneficiary wallet
+    pub destination: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = vasp_authority,
+        space = TravelRuleRecord::LEN,
+        seeds = [
+            b"travel_rule",
+            mint.key().as_ref(),
+            source.key().as_ref(),
+            destination.key().as_ref()
+        ],
+        bump
+    )]
+    pub travel_rule_record: Account<'info, TravelRuleRecord>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdraw a pending travel-rule record between a source and destination wallet
+#[derive(Accounts)]
+pub struct CancelTravelRuleRecord<'info> {
+    #[account(
+        has_one = vasp_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub vasp_authority: Signer<'info>,
+    /// CHECK: The originator wallet
+    pub source: AccountInfo<'info>,
+    /// CHECK: The beneficiary wallet
+    pub destination: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [
+            b"travel_rule",
+            mint.key().as_ref(),
+            source.key().as_ref(),
+            destination.key().as_ref()
+        ],
+        bump
+    )]
+    pub travel_rule_record: Account<'info, TravelRuleRecord>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Approve a program for the destination program allowlist
+#[derive(Accounts)]
+pub struct ApproveProgram<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = ProgramRegistry::LEN,
+        seeds = [b"program_registry", mint.key().as_ref()],
+        bump
+    )]
+    pub program_registry: Account<'info, ProgramRegistry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Revoke a program's approval
+#[derive(Accounts)]
+pub struct RevokeProgram<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_registry", mint.key().as_ref()],
+        bump
+    )]
+    pub program_registry: Account<'info, ProgramRegistry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Exempt a wallet from velocity limits
+#[derive(Accounts)]
+pub struct AddVelocityExemption<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The treasury or exchange wallet
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8, // Just the discriminator
+        seeds = [b"velocity_exempt", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub velocity_exemption: Account<'info, VelocityExemption>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remove a wallet's velocity exemption
+#[derive(Accounts)]
+pub struct RemoveVelocityExemption<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: The wallet losing its exemption
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"velocity_exempt", mint.key().as_ref(), wallet.key().as_ref()],
+        bump,
+        close = authority
+    )]
+    pub velocity_exemption: Account<'info, VelocityExemption>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Create a wallet's velocity state
+#[derive(Accounts)]
+pub struct OpenVelocityState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: The wallet whose outbound transfers are tracked
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = VelocityState::LEN,
+        seeds = [b"velocity", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub velocity_state: Account<'info, VelocityState>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Add an address to the blacklist
+#[derive(Accounts)]
+pub struct AddToBlacklist<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The address being blacklisted
+    pub target_address: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = BlacklistEntry::LEN,
+        seeds = [b"blacklist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remove an address from the blacklist
+#[derive(Accounts)]
+pub struct RemoveFromBlacklist<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The address being removed from blacklist
+    pub target_address: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"blacklist", mint.key().as_ref(), target_address.key().as_ref()],
         bump,
         close = authority
     )]
@@ -227,15 +1848,263 @@ pub struct RemoveFromBlacklist<'info> {
     pub mint: InterfaceAccount<'info, Mint>,
 }
 
-/// Context for the transfer hook execution
+/// Add several addresses to the blacklist; `[target, entry]` pairs follow as remaining accounts
+#[derive(Accounts)]
+pub struct AddToBlacklistBatch<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remove several addresses from the blacklist; `[target, entry]` pairs follow as remaining accounts
+#[derive(Accounts)]
+pub struct RemoveFromBlacklistBatch<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Move a legacy `[b"blacklist", target]` entry under `[b"blacklist", mint, target]`
+#[derive(Accounts)]
+pub struct MigrateBlacklistEntry<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The address whose entry is being migrated
+    pub target_address: AccountInfo<'info>,
+    /// CHECK: Legacy marker entry, read by hand since it predates the current layout
+    #[account(
+        owner = crate::ID,
+        seeds = [b"blacklist", target_address.key().as_ref()],
+        bump
+    )]
+    pub legacy_blacklist_entry: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = BlacklistEntry::LEN,
+        seeds = [b"blacklist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Close a legacy `[b"blacklist", target]` entry already migrated for this mint
+#[derive(Accounts)]
+pub struct CloseLegacyBlacklistEntry<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The address whose legacy entry is being closed
+    pub target_address: AccountInfo<'info>,
+    /// CHECK: Legacy marker entry, closed by hand since it predates the current layout
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"blacklist", target_address.key().as_ref()],
+        bump
+    )]
+    pub legacy_blacklist_entry: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"blacklist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Grow a marker-only per-mint blacklist entry to the current layout
+#[derive(Accounts)]
+pub struct ResizeBlacklistEntry<'info> {
+    #[account(
+        has_one = authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// gold_token PDA proving the call comes through the controller program
+    #[account(
+        seeds = [CONTROLLER_AUTHORITY_SEED],
+        bump,
+        seeds::program = config.controller_program
+    )]
+    pub controller: Signer<'info>,
+    /// CHECK: The address whose entry is being resized
+    pub target_address: AccountInfo<'info>,
+    /// CHECK: Marker-only entry, deserialized by hand since it is shorter than `BlacklistEntry::LEN`
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"blacklist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump
+    )]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Add an address to the KYC allowlist
+#[derive(Accounts)]
+pub struct AddToAllowlist<'info> {
+    #[account(
+        has_one = compliance_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    /// CHECK: The address being allowlisted
+    pub target_address: AccountInfo<'info>,
+    #[account(
+        init,
+        payer = compliance_authority,
+        space = 8, // Just the discriminator
+        seeds = [b"allowlist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Remove an address from the KYC allowlist
+#[derive(Accounts)]
+pub struct RemoveFromAllowlist<'info> {
+    #[account(
+        has_one = compliance_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    /// CHECK: The address being removed from the allowlist
+    pub target_address: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), target_address.key().as_ref()],
+        bump,
+        close = compliance_authority
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Create or refresh a KYC attestation
+#[derive(Accounts)]
+pub struct SetKycAttestation<'info> {
+    #[account(
+        has_one = compliance_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    /// CHECK: The verified wallet
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = compliance_authority,
+        space = KycAttestation::LEN,
+        seeds = [b"kyc", mint.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub kyc_attestation: Account<'info, KycAttestation>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Close a KYC attestation
+#[derive(Accounts)]
+pub struct RevokeKycAttestation<'info> {
+    #[account(
+        has_one = compliance_authority,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub compliance_authority: Signer<'info>,
+    /// CHECK: The wallet whose attestation is revoked
+    pub wallet: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"kyc", mint.key().as_ref(), wallet.key().as_ref()],
+        bump,
+        close = compliance_authority
+    )]
+    pub kyc_attestation: Account<'info, KycAttestation>,
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+/// Context for the transfer hook execution.
+/// The accounts of each policy in the chain follow as remaining accounts.
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
-    #[account(token::mint = mint, token::authority = owner)]
+    #[account(token::mint = mint)]
     pub source_token: InterfaceAccount<'info, TokenAccount>,
     pub mint: InterfaceAccount<'info, Mint>,
     #[account(token::mint = mint)]
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
-    /// CHECK: source token account owner
+    /// CHECK: transfer authority, the source token account owner or a delegate;
+    /// Token-2022 has already verified it
     pub owner: UncheckedAccount<'info>,
     /// CHECK: ExtraAccountMetaList Account
     #[account(
@@ -245,27 +2114,387 @@ pub struct TransferHook<'info> {
     pub extra_account_meta_list: UncheckedAccount<'info>,
     /// CHECK: Source blacklist PDA
     #[account(
-        seeds = [b"blacklist", source_token.owner.as_ref()],
+        seeds = [b"blacklist", mint.key().as_ref(), source_token.owner.as_ref()],
         bump
     )]
     pub source_blacklist_entry: UncheckedAccount<'info>,
     /// CHECK: Destination blacklist PDA
     #[account(
-        seeds = [b"blacklist", destination_token.owner.as_ref()],
+        seeds = [b"blacklist", mint.key().as_ref(), destination_token.owner.as_ref()],
         bump
     )]
     pub destination_blacklist_entry: UncheckedAccount<'info>,
+    /// CHECK: Controlling program's config, pinned by the ExtraAccountMetaList
+    pub pause_config: UncheckedAccount<'info>,
+    #[account(
+        has_one = mint,
+        seeds = [b"config", mint.key().as_ref()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: Transfer authority blacklist PDA
+    #[account(
+        seeds = [b"blacklist", mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub authority_blacklist_entry: UncheckedAccount<'info>,
+}
+
+impl TransferHook<'_> {
+    /// Deny the transfer, or only report the violation while `rule` is monitored
+    fn violation(&self, rule: Rule, error: GatekeeperError, reason: &str, amount: u64) -> Result<()> {
+        if self.config.rule_modes[rule as usize] == RuleMode::Monitor {
+            msg!("{} Monitoring only.", reason);
+            emit!(PolicyViolation {
+                rule,
+                mint: self.mint.key(),
+                source_owner: self.source_token.owner,
+                destination_owner: self.destination_token.owner,
+                amount,
+            });
+            return Ok(());
+        }
+        msg!("{} Transfer denied.", reason);
+        Err(error.into())
+    }
 }
 
 /// Configuration account for the gatekeeper
 #[account]
+#[derive(Default)]
 pub struct Config {
     pub authority: Pubkey,
+    /// Manages the KYC allowlist
+    pub compliance_authority: Pubkey,
+    /// Lowest attestation tier that may receive while `Rule::Kyc` runs
+    pub min_kyc_tier: u8,
+    /// Maximum balance per attestation tier, 0 for no limit
+    pub kyc_tier_max_balance: [u64; KYC_TIER_COUNT],
+    /// Program whose `CONTROLLER_AUTHORITY_SEED` PDA must sign blacklist changes
+    pub controller_program: Pubkey,
+    /// Mint this config governs, checked on every transfer
+    pub mint: Pubkey,
+    /// Maximum outbound amount over the trailing `VELOCITY_WINDOW` for each sender tier, 0 for no limit
+    pub velocity_limit: [u64; KYC_TIER_COUNT],
+    /// Maximum balance any holder may reach, 0 for no limit
+    pub max_balance: u64,
+    /// Maximum number of non-empty token accounts, 0 for no limit
+    pub max_holders: u64,
+    /// Non-empty token accounts, maintained by the hook
+    pub holder_count: u64,
+    /// Seconds newly minted tokens stay locked
+    pub lockup_period: i64,
+    /// Rules checked on every transfer, in order. The pause and the blacklist are not part of
+    /// the chain: they run first on every transfer and cannot be reordered or switched off.
+    pub policies: Vec<Rule>,
+    /// Transfers above this amount need a `TravelRuleRecord`, 0 for no threshold
+    pub travel_rule_threshold: u64,
+    /// VASP role that registers travel-rule records
+    pub vasp_authority: Pubkey,
+    /// `controller_program`'s review escrow PDA, whose transfers skip the velocity limit
+    pub review_escrow: Pubkey,
+    /// How each `Rule` is applied, indexed by rule; one slot per rule the chain can hold
+    pub rule_modes: [RuleMode; MAX_POLICIES],
+}
+
+impl Config {
+    /// Length of configs created by the original program, which held only the authority
+    pub const BASELINE_LEN: usize = 8 + 32;
+    pub const LEN: usize = 8 + 32 + 32 + 1 + 8 * KYC_TIER_COUNT + 32 + 32 + 8 * KYC_TIER_COUNT
+        + 8 + 8 + 8 + 8 + 4 + MAX_POLICIES + 8 + 32 + 32 + MAX_POLICIES;
+
+    fn enabled(&self, rule: Rule) -> bool {
+        self.rule_modes[rule as usize] != RuleMode::Off
+    }
+}
+
+/// Most rules a policy chain can hold, one of each; also sizes `Config::rule_modes`
+pub const MAX_POLICIES: usize = 8;
+
+/// Most programs the destination program allowlist can hold
+pub const MAX_APPROVED_PROGRAMS: usize = 16;
+
+/// Chain given to new configs; the lockup is always needed for minted tokens
+pub const DEFAULT_POLICIES: [Rule; 1] = [Rule::Lockup];
+
+/// Transfer rules that can be monitored or switched off; the blacklist and pause always apply
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    Allowlist,
+    Kyc,
+    Velocity,
+    Lockup,
+    MaxBalance,
+    HolderLimit,
+    TravelRule,
+    ProgramAllowlist,
+}
+
+/// How a rule treats a transfer that breaks it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RuleMode {
+    /// Deny the transfer
+    #[default]
+    Enforce,
+    /// Approve the transfer but emit `PolicyViolation`
+    Monitor,
+    /// Skip the rule
+    Off,
+}
+
+/// A monitored rule would have denied this transfer
+#[event]
+pub struct PolicyViolation {
+    pub rule: Rule,
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TravelRuleRecordConsumed {
+    pub mint: Pubkey,
+    pub source_owner: Pubkey,
+    pub destination_owner: Pubkey,
+    pub nonce: u64,
+    pub amount: u64,
+    pub payload_hash: [u8; 32],
+}
+
+/// Length of the rolling velocity window in seconds
+pub const VELOCITY_WINDOW: i64 = 24 * 60 * 60;
+
+/// Length of each velocity bucket in seconds; the window is the current bucket and the ones before it
+pub const VELOCITY_BUCKET: i64 = 60 * 60;
+
+/// Number of buckets covering the velocity window
+pub const VELOCITY_BUCKETS: usize = (VELOCITY_WINDOW / VELOCITY_BUCKET) as usize;
+
+/// Number of travel-rule records a wallet pair may have waiting for their transfers
+pub const MAX_PENDING_TRAVEL_RULE_RECORDS: usize = 4;
+
+/// Number of separately timed mints a lockup tracks at once
+pub const MAX_LOCK_TRANCHES: usize = 8;
+
+/// Number of KYC tiers, numbered from 0
+pub const KYC_TIER_COUNT: usize = 4;
+
+/// Marks an address as blacklisted for a single mint, with the case behind it
+#[account]
+#[derive(Default)]
+pub struct BlacklistEntry {
+    /// Compliance reason code, defined off chain
+    pub reason_code: u16,
+    /// Hash of the compliance case reference
+    pub case_reference: [u8; 32],
+    pub added_by: Pubkey,
+    pub added_at: i64,
+}
+
+impl BlacklistEntry {
+    pub const LEN: usize = 8 + 2 + 32 + 32 + 8;
+
+    fn record(&mut self, reason_code: u16, case_reference: [u8; 32], added_by: Pubkey) -> Result<()> {
+        self.reason_code = reason_code;
+        self.case_reference = case_reference;
+        self.added_by = added_by;
+        self.added_at = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+}
+
+/// Empty account that marks an address as KYC-verified for a single mint
+#[account]
+pub struct AllowlistEntry {}
+
+/// KYC verification of a wallet for a single mint
+#[account]
+pub struct KycAttestation {
+    pub tier: u8,
+    /// Identity provider that performed the verification
+    pub provider: Pubkey,
+    /// ISO 3166-1 alpha-2 country code
+    pub country_code: [u8; 2],
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+impl KycAttestation {
+    pub const LEN: usize = 8 + 1 + 32 + 2 + 8 + 8;
+}
+
+/// Outbound volume of a wallet over the trailing velocity window, kept in hourly buckets
+#[account]
+#[derive(Default)]
+pub struct VelocityState {
+    /// Start of the latest bucket written to
+    pub window_start: i64,
+    /// Outbound volume across all buckets
+    pub outbound: u64,
+    /// Outbound volume per bucket, indexed by `bucket_index` of the bucket's start
+    pub buckets: [u64; VELOCITY_BUCKETS],
+}
+
+impl VelocityState {
+    pub const LEN: usize = 8 + 8 + 8 + 8 * VELOCITY_BUCKETS;
+
+    pub fn bucket_index(timestamp: i64) -> usize {
+        timestamp.div_euclid(VELOCITY_BUCKET).rem_euclid(VELOCITY_BUCKETS as i64) as usize
+    }
+
+    /// Add `amount` sent at `now`, dropping buckets that have left the window
+    pub fn record(&mut self, now: i64, amount: u64) {
+        let latest = self.window_start.div_euclid(VELOCITY_BUCKET);
+        let current = now.div_euclid(VELOCITY_BUCKET).max(latest);
+        let expired = (current - latest).min(VELOCITY_BUCKETS as i64);
+        for bucket in latest + 1..=latest + expired {
+            self.buckets[Self::bucket_index(bucket * VELOCITY_BUCKET)] = 0;
+        }
+        self.window_start = current * VELOCITY_BUCKET;
+
+        let bucket = &mut self.buckets[Self::bucket_index(self.window_start)];
+        *bucket = bucket.saturating_add(amount);
+        self.outbound = self.buckets.iter().fold(0, |sum, &volume| sum.saturating_add(volume));
+    }
+}
+
+/// Tokens from a single mint that may not move before `unlock_at`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct LockTranche {
+    pub amount: u64,
+    pub unlock_at: i64,
+}
+
+/// Minted tokens a wallet may not move, one tranche per mint
+#[account]
+#[derive(Default)]
+pub struct Lockup {
+    /// Amount still locked across all tranches as of the latest lock
+    pub locked_amount: u64,
+    /// Unlock time of the latest tranche
+    pub unlock_at: i64,
+    /// Running tranches; unused slots are zeroed
+    pub tranches: [LockTranche; MAX_LOCK_TRANCHES],
+}
+
+impl Lockup {
+    pub const LEN: usize = 8 + 8 + 8 + 16 * MAX_LOCK_TRANCHES;
+
+    /// Amount still locked at `now`
+    pub fn locked_at(&self, now: i64) -> u64 {
+        self.tranches
+            .iter()
+            .filter(|tranche| tranche.unlock_at > now)
+            .fold(0, |sum, tranche| sum.saturating_add(tranche.amount))
+    }
+
+    /// Add a tranche of `amount` unlocking at `unlock_at`, dropping tranches that have expired.
+    /// When every slot is taken, the earliest tranche is folded into the next one, so no
+    /// tokens unlock earlier than they were locked for.
+    pub fn lock(&mut self, now: i64, amount: u64, unlock_at: i64) {
+        let mut running: Vec<LockTranche> = self
+            .tranches
+            .iter()
+            .copied()
+            .filter(|tranche| tranche.amount > 0 && tranche.unlock_at > now)
+            .collect();
+        running.push(LockTranche { amount, unlock_at });
+        running.sort_by_key(|tranche| tranche.unlock_at);
+        if running.len() > MAX_LOCK_TRANCHES {
+            let earliest = running.remove(0);
+            running[0].amount = running[0].amount.saturating_add(earliest.amount);
+        }
+
+        self.tranches = Default::default();
+        self.tranches[..running.len()].copy_from_slice(&running);
+        self.locked_amount = self.locked_at(now);
+        self.unlock_at = running.last().map_or(unlock_at, |tranche| tranche.unlock_at);
+    }
+}
+
+/// Originator and beneficiary information registered for one above-threshold transfer
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct TravelRuleEntry {
+    /// Nonce the VASP registered the entry under, 0 for an unused slot
+    pub nonce: u64,
+    /// Exact amount of the transfer the entry covers
+    pub amount: u64,
+    /// Hash of the IVMS101 payload exchanged between the VASPs
+    pub payload_hash: [u8; 32],
+    pub registered_at: i64,
+}
+
+/// Travel-rule records registered for a transfer between two wallets. The hook can only
+/// resolve accounts from the transfer itself, which carries no nonce, so a PDA per nonce
+/// could never be found; every nonce of the pair shares this account instead. The VASP
+/// cancels entries whose transfer will not happen, so the pending slots never fill up for good.
+#[account]
+#[derive(Default)]
+pub struct TravelRuleRecord {
+    /// Increases with every record registered for the pair
+    pub nonce: u64,
+    /// Amount of the latest record
+    pub amount: u64,
+    /// Payload hash of the latest record
+    pub payload_hash: [u8; 32],
+    /// Registration time of the latest record
+    pub registered_at: i64,
+    /// Set once a transfer has used the latest record
+    pub consumed: bool,
+    /// Records no transfer has used yet, oldest first; unused slots are zeroed
+    pub pending: [TravelRuleEntry; MAX_PENDING_TRAVEL_RULE_RECORDS],
+}
+
+impl TravelRuleRecord {
+    pub const LEN: usize = 8 + 8 + 8 + 32 + 8 + 1 + 56 * MAX_PENDING_TRAVEL_RULE_RECORDS;
+
+    /// Take the oldest pending entry covering exactly `amount`
+    pub fn consume(&mut self, amount: u64) -> Option<TravelRuleEntry> {
+        let slot = self
+            .pending
+            .iter()
+            .position(|entry| entry.nonce != 0 && entry.amount == amount)?;
+        Some(self.remove(slot))
+    }
+
+    /// Drop the pending entry registered under `nonce`
+    pub fn cancel(&mut self, nonce: u64) -> Option<TravelRuleEntry> {
+        if nonce == 0 {
+            return None;
+        }
+        let slot = self.pending.iter().position(|entry| entry.nonce == nonce)?;
+        Some(self.remove(slot))
+    }
+
+    /// Take the pending entry at `slot`, keeping the rest in order. Once the latest entry
+    /// is gone no transfer may use it.
+    fn remove(&mut self, slot: usize) -> TravelRuleEntry {
+        let entry = self.pending[slot];
+        self.pending.copy_within(slot + 1.., slot);
+        self.pending[MAX_PENDING_TRAVEL_RULE_RECORDS - 1] = TravelRuleEntry::default();
+        if entry.nonce == self.nonce {
+            self.consumed = true;
+        }
+        entry
+    }
+}
+
+/// Programs whose accounts may hold the mint, such as vetted pools and lending markets,
+/// and program-derived addresses approved on their own
+#[account]
+#[derive(Default)]
+pub struct ProgramRegistry {
+    pub programs: Vec<Pubkey>,
+}
+
+impl ProgramRegistry {
+    pub const LEN: usize = 8 + 4 + 32 * MAX_APPROVED_PROGRAMS;
 }
 
-/// Empty account that marks an address as blacklisted
+/// Empty account that exempts a treasury or exchange wallet from velocity limits
 #[account]
-pub struct BlacklistEntry {}
+pub struct VelocityExemption {}
 
 #[error_code]
 pub enum GatekeeperError {
@@ -273,4 +2502,66 @@ pub enum GatekeeperError {
     AddressBlacklisted,
     #[msg("Unauthorized: The signer is not the configured authority.")]
     Unauthorized,
+    #[msg("Transfers are paused.")]
+    TransfersPaused,
+    #[msg("The pause config account does not hold a pause flag.")]
+    InvalidPauseConfig,
+    #[msg("The address is not on the KYC allowlist.")]
+    AddressNotAllowlisted,
+    #[msg("The account is not a gatekeeper config.")]
+    InvalidConfig,
+    #[msg("The account is not a marker-only blacklist entry.")]
+    InvalidBlacklistEntry,
+    #[msg("Batch accounts must be [target, blacklist entry] pairs for this mint.")]
+    InvalidBatch,
+    #[msg("The address is already on the transfer blacklist.")]
+    AlreadyBlacklisted,
+    #[msg("The address is not on the transfer blacklist.")]
+    NotBlacklisted,
+    #[msg("KYC tier is out of range.")]
+    InvalidKycTier,
+    #[msg("The address has no KYC attestation.")]
+    KycMissing,
+    #[msg("The KYC attestation has expired.")]
+    KycExpired,
+    #[msg("The KYC tier is below the required minimum.")]
+    KycTierTooLow,
+    #[msg("The balance exceeds the limit for this KYC tier.")]
+    KycBalanceLimitExceeded,
+    #[msg("The mint's transfer hook does not point at this program.")]
+    HookNotConfigured,
+    #[msg("The hook was not invoked by a Token-2022 transfer.")]
+    NotTransferring,
+    #[msg("The sender has no velocity state.")]
+    VelocityStateMissing,
+    #[msg("The transfer exceeds the sender's velocity limit.")]
+    VelocityLimitExceeded,
+    #[msg("The destination balance exceeds the per-holder maximum.")]
+    MaxBalanceExceeded,
+    #[msg("The transfer would exceed the maximum number of holders.")]
+    HolderLimitExceeded,
+    #[msg("The lockup period cannot be negative.")]
+    InvalidLockupPeriod,
+    #[msg("The transfer would move locked tokens.")]
+    TokensLocked,
+    #[msg("A policy account does not match the chain.")]
+    InvalidPolicyAccount,
+    #[msg("The policy chain is too long or repeats a rule.")]
+    InvalidPolicyChain,
+    #[msg("Travel-rule nonces must increase.")]
+    InvalidTravelRuleNonce,
+    #[msg("The transfer needs a matching travel-rule record.")]
+    TravelRuleRecordMissing,
+    #[msg("The program registry is full.")]
+    ProgramRegistryFull,
+    #[msg("The destination is owned by an unapproved program.")]
+    DestinationProgramNotApproved,
+    #[msg("The account is not a lockup.")]
+    InvalidLockup,
+    #[msg("The pair already has the maximum number of pending travel-rule records.")]
+    TravelRuleRecordsFull,
+    #[msg("A controller program must be given.")]
+    InvalidControllerProgram,
+    #[msg("No pending travel-rule record has this nonce.")]
+    TravelRuleRecordNotPending,
 }
\ No newline at end of file
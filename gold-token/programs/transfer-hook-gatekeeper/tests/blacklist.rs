@@ -0,0 +1,328 @@
+//! Blacklist changes must be co-signed by the controller program's PDA, and legacy
+//! global entries survive migration until closed explicitly.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{account_info, install_stubs, pda_account, signer_info, system_program, Gatekeeper};
+use transfer_hook_gatekeeper::{instruction, BlacklistEntry, GatekeeperError, ID};
+
+fn remove(
+    gatekeeper: &Gatekeeper,
+    controller: AccountInfo<'static>,
+) -> std::result::Result<(), ProgramError> {
+    let target = Pubkey::new_unique();
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        controller,
+        account_info(target, System::id(), vec![]),
+        pda_account(
+            gatekeeper.pda(b"blacklist", &target),
+            Some(&BlacklistEntry::default()),
+            BlacklistEntry::LEN,
+        ),
+        gatekeeper.mint_account(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::RemoveFromBlacklist {}.data(),
+    )
+}
+
+/// The legacy global entry for `target`, marker-only as it was written
+fn legacy_entry(target: &Pubkey) -> AccountInfo<'static> {
+    let key = Pubkey::find_program_address(&[b"blacklist", target.as_ref()], &ID).0;
+    let mut info = account_info(key, ID, BlacklistEntry::DISCRIMINATOR.to_vec());
+    info.is_writable = true;
+    info
+}
+
+fn migrate(
+    gatekeeper: &Gatekeeper,
+    target: Pubkey,
+    legacy: AccountInfo<'static>,
+) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        gatekeeper.controller(),
+        account_info(target, System::id(), vec![]),
+        legacy,
+        pda_account::<BlacklistEntry>(
+            gatekeeper.pda(b"blacklist", &target),
+            None,
+            BlacklistEntry::LEN,
+        ),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::MigrateBlacklistEntry {
+            reason_code: 1,
+            case_reference: [0; 32],
+        }
+        .data(),
+    )
+}
+
+fn close_legacy(
+    gatekeeper: &Gatekeeper,
+    target: Pubkey,
+    legacy: AccountInfo<'static>,
+    migrated: Option<BlacklistEntry>,
+) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        gatekeeper.controller(),
+        account_info(target, System::id(), vec![]),
+        legacy,
+        pda_account(
+            gatekeeper.pda(b"blacklist", &target),
+            migrated.as_ref(),
+            BlacklistEntry::LEN,
+        ),
+        gatekeeper.mint_account(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::CloseLegacyBlacklistEntry {}.data(),
+    )
+}
+
+/// `[target, entry]` pairs for fresh targets, entries not yet created
+fn batch(gatekeeper: &Gatekeeper, targets: usize) -> Vec<AccountInfo<'static>> {
+    (0..targets)
+        .flat_map(|_| {
+            let target = Pubkey::new_unique();
+            let mut entry =
+                account_info(gatekeeper.pda(b"blacklist", &target), System::id(), vec![]);
+            entry.is_writable = true;
+            [account_info(target, System::id(), vec![]), entry]
+        })
+        .collect()
+}
+
+fn run_batch(
+    gatekeeper: &Gatekeeper,
+    fixed: Vec<AccountInfo<'static>>,
+    pairs: &[AccountInfo<'static>],
+    data: Vec<u8>,
+) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let mut accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        gatekeeper.controller(),
+        gatekeeper.mint_account(),
+    ];
+    accounts.extend(fixed);
+    accounts.extend_from_slice(pairs);
+    transfer_hook_gatekeeper::entry(&ID, Box::leak(accounts.into_boxed_slice()), &data)
+}
+
+fn add_batch(
+    gatekeeper: &Gatekeeper,
+    pairs: &[AccountInfo<'static>],
+) -> std::result::Result<(), ProgramError> {
+    let data = instruction::AddToBlacklistBatch {
+        reason_code: 7,
+        case_reference: [3; 32],
+    }
+    .data();
+    run_batch(gatekeeper, vec![system_program()], pairs, data)
+}
+
+fn remove_batch(
+    gatekeeper: &Gatekeeper,
+    pairs: &[AccountInfo<'static>],
+) -> std::result::Result<(), ProgramError> {
+    let data = instruction::RemoveFromBlacklistBatch {}.data();
+    run_batch(gatekeeper, vec![], pairs, data)
+}
+
+fn resize(
+    gatekeeper: &Gatekeeper,
+    target: Pubkey,
+    entry: AccountInfo<'static>,
+) -> std::result::Result<(), ProgramError> {
+    install_stubs();
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        gatekeeper.controller(),
+        account_info(target, System::id(), vec![]),
+        entry,
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::ResizeBlacklistEntry {
+            reason_code: 7,
+            case_reference: [3; 32],
+        }
+        .data(),
+    )
+}
+
+fn anchor_error(code: ErrorCode) -> std::result::Result<(), ProgramError> {
+    Err(ProgramError::Custom(code.into()))
+}
+
+#[test]
+fn direct_call_without_controller_signature_is_rejected() {
+    let gatekeeper = Gatekeeper::new();
+    let mut controller = account_info(gatekeeper.controller_pda(), System::id(), vec![]);
+    controller.is_signer = false;
+    assert_eq!(
+        remove(&gatekeeper, controller),
+        anchor_error(ErrorCode::AccountNotSigner)
+    );
+}
+
+#[test]
+fn signer_that_is_not_the_controller_pda_is_rejected() {
+    let gatekeeper = Gatekeeper::new();
+    let impostor = signer_info(Pubkey::new_unique());
+    assert_eq!(
+        remove(&gatekeeper, impostor),
+        anchor_error(ErrorCode::ConstraintSeeds)
+    );
+}
+
+#[test]
+fn migration_leaves_the_legacy_entry_for_other_mints() {
+    let target = Pubkey::new_unique();
+    let legacy = legacy_entry(&target);
+    for gatekeeper in [Gatekeeper::new(), Gatekeeper::new()] {
+        assert_eq!(migrate(&gatekeeper, target, legacy.clone()), Ok(()));
+    }
+    assert_eq!(*legacy.owner, ID);
+    assert_eq!(
+        legacy.try_borrow_data().unwrap()[..],
+        BlacklistEntry::DISCRIMINATOR[..]
+    );
+}
+
+#[test]
+fn migrated_legacy_entry_can_be_closed() {
+    let gatekeeper = Gatekeeper::new();
+    let target = Pubkey::new_unique();
+    let legacy = legacy_entry(&target);
+    assert_eq!(
+        close_legacy(
+            &gatekeeper,
+            target,
+            legacy.clone(),
+            Some(BlacklistEntry::default())
+        ),
+        Ok(())
+    );
+    assert_eq!(*legacy.owner, System::id());
+    assert_eq!(legacy.lamports(), 0);
+}
+
+#[test]
+fn legacy_entry_cannot_be_closed_before_migration() {
+    let gatekeeper = Gatekeeper::new();
+    let target = Pubkey::new_unique();
+    assert_eq!(
+        close_legacy(&gatekeeper, target, legacy_entry(&target), None),
+        anchor_error(ErrorCode::AccountNotInitialized)
+    );
+}
+
+#[test]
+fn batch_add_records_an_entry_per_pair() {
+    let gatekeeper = Gatekeeper::new();
+    let pairs = batch(&gatekeeper, 2);
+    assert_eq!(add_batch(&gatekeeper, &pairs), Ok(()));
+    for entry in pairs.iter().skip(1).step_by(2) {
+        assert_eq!(*entry.owner, ID);
+        let entry = BlacklistEntry::try_deserialize(&mut &entry.try_borrow_data().unwrap()[..]);
+        let entry = entry.unwrap();
+        assert_eq!(entry.reason_code, 7);
+        assert_eq!(entry.added_by, gatekeeper.authority());
+    }
+}
+
+#[test]
+fn batch_needs_whole_pairs() {
+    let gatekeeper = Gatekeeper::new();
+    let invalid = Err(ProgramError::Custom(GatekeeperError::InvalidBatch.into()));
+    let pairs = batch(&gatekeeper, 2);
+    assert_eq!(add_batch(&gatekeeper, &pairs[..3]), invalid);
+    assert_eq!(add_batch(&gatekeeper, &[]), invalid);
+    assert_eq!(remove_batch(&gatekeeper, &pairs[..1]), invalid);
+}
+
+#[test]
+fn batch_entry_must_be_the_targets_pda() {
+    let gatekeeper = Gatekeeper::new();
+    let mut pairs = batch(&gatekeeper, 2);
+    pairs.swap(1, 3);
+    assert_eq!(
+        add_batch(&gatekeeper, &pairs),
+        Err(ProgramError::Custom(GatekeeperError::InvalidBatch.into()))
+    );
+}
+
+#[test]
+fn batch_remove_closes_every_entry() {
+    let gatekeeper = Gatekeeper::new();
+    let pairs = batch(&gatekeeper, 2);
+    add_batch(&gatekeeper, &pairs).unwrap();
+    assert_eq!(remove_batch(&gatekeeper, &pairs), Ok(()));
+    for entry in pairs.iter().skip(1).step_by(2) {
+        assert_eq!(*entry.owner, System::id());
+        assert!(entry.data_is_empty());
+    }
+    assert_eq!(
+        remove_batch(&gatekeeper, &pairs),
+        Err(ProgramError::Custom(GatekeeperError::NotBlacklisted.into()))
+    );
+}
+
+#[test]
+fn marker_entry_is_resized_with_case_details() {
+    let gatekeeper = Gatekeeper::new();
+    let target = Pubkey::new_unique();
+    let mut entry = account_info(
+        gatekeeper.pda(b"blacklist", &target),
+        ID,
+        BlacklistEntry::DISCRIMINATOR.to_vec(),
+    );
+    entry.is_writable = true;
+    assert_eq!(resize(&gatekeeper, target, entry.clone()), Ok(()));
+    let data = entry.try_borrow_data().unwrap();
+    assert_eq!(data.len(), BlacklistEntry::LEN);
+    let entry = BlacklistEntry::try_deserialize(&mut &data[..]).unwrap();
+    assert_eq!(entry.reason_code, 7);
+    assert_eq!(entry.case_reference, [3; 32]);
+}
+
+#[test]
+fn full_entry_is_not_resized() {
+    let gatekeeper = Gatekeeper::new();
+    let target = Pubkey::new_unique();
+    let entry = pda_account(
+        gatekeeper.pda(b"blacklist", &target),
+        Some(&BlacklistEntry::default()),
+        BlacklistEntry::LEN,
+    );
+    assert_eq!(
+        resize(&gatekeeper, target, entry),
+        Err(ProgramError::Custom(
+            GatekeeperError::InvalidBlacklistEntry.into()
+        ))
+    );
+}
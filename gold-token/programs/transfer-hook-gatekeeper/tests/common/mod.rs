@@ -0,0 +1,233 @@
+//! Helpers for driving the gatekeeper entrypoint without a validator.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+        instruction::Instruction,
+        program_option::COption,
+        program_pack::Pack,
+        program_stubs,
+    },
+};
+use anchor_spl::token_2022::{spl_token_2022::state::Mint, ID as TOKEN_2022_PROGRAM_ID};
+use transfer_hook_gatekeeper::{
+    controller_config, review_escrow, Config, CONTROLLER_AUTHORITY_SEED, ID,
+};
+
+/// Unix timestamp every test observes through `Clock::get`
+pub const NOW: i64 = 1_760_000_000;
+
+struct Stubs;
+
+impl program_stubs::SyscallStubs for Stubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    /// Emulates `system_program::create_account` so `init` constraints and
+    /// hand-created PDAs succeed; every other CPI is a no-op
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let data = &instruction.data;
+        if instruction.program_id != System::id() || data[..4] != [0; 4] {
+            return Ok(());
+        }
+        let lamports = u64::from_le_bytes(data[4..12].try_into().unwrap());
+        let space = u64::from_le_bytes(data[12..20].try_into().unwrap());
+        let owner = Pubkey::try_from(&data[20..52]).unwrap();
+        let find = |key: &Pubkey| account_infos.iter().find(|info| info.key == key).unwrap();
+        let (payer, created) = (
+            find(&instruction.accounts[0].pubkey),
+            find(&instruction.accounts[1].pubkey),
+        );
+        **payer.try_borrow_mut_lamports()? -= lamports;
+        **created.try_borrow_mut_lamports()? += lamports;
+        created.assign(&owner);
+        created.resize(space as usize)
+    }
+}
+
+pub fn install_stubs() {
+    static INSTALL: std::sync::Once = std::sync::Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+pub fn leak<T>(value: T) -> &'static mut T {
+    Box::leak(Box::new(value))
+}
+
+/// Lays `key` and `data` out the way the runtime serializes them, so `resize` can
+/// record the new length in front of the data and grow into spare room
+fn serialized(key: Pubkey, data: Vec<u8>) -> (&'static Pubkey, &'static mut [u8]) {
+    #[repr(C)]
+    struct SerializedKey {
+        original_data_len: u32,
+        key: Pubkey,
+    }
+    let header = leak(SerializedKey {
+        original_data_len: data.len() as u32,
+        key,
+    });
+    let words = (8 + data.len() + MAX_PERMITTED_DATA_INCREASE).div_ceil(8);
+    let buffer = Box::leak(vec![0u64; words].into_boxed_slice());
+    buffer[0] = data.len() as u64;
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(buffer.as_mut_ptr().add(1) as *mut u8, data.len())
+    };
+    bytes.copy_from_slice(&data);
+    (&header.key, bytes)
+}
+
+pub fn account_info(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    let lamports = if data.is_empty() { 0 } else { 1_000_000_000 };
+    let (key, data) = serialized(key, data);
+    AccountInfo::new(
+        key,
+        false,
+        false,
+        leak(lamports),
+        data,
+        leak(owner),
+        false,
+        0,
+    )
+}
+
+pub fn signer_info(key: Pubkey) -> AccountInfo<'static> {
+    let (key, data) = serialized(key, vec![]);
+    AccountInfo::new(
+        key,
+        true,
+        true,
+        leak(1_000_000_000),
+        data,
+        leak(System::id()),
+        false,
+        0,
+    )
+}
+
+pub fn mint_account(key: Pubkey) -> AccountInfo<'static> {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 1_000_000_000_000,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    account_info(key, TOKEN_2022_PROGRAM_ID, data)
+}
+
+pub fn system_program() -> AccountInfo<'static> {
+    let mut info = account_info(System::id(), Pubkey::default(), vec![]);
+    info.executable = true;
+    info
+}
+
+/// Writable gatekeeper PDA holding `existing`, or an unfunded system account of
+/// `len` bytes for `init` to create
+pub fn pda_account<T: AccountSerialize>(
+    key: Pubkey,
+    existing: Option<&T>,
+    len: usize,
+) -> AccountInfo<'static> {
+    let mut info = match existing {
+        Some(state) => {
+            let mut data = vec![0; len];
+            state.try_serialize(&mut &mut data[..]).unwrap();
+            account_info(key, ID, data)
+        }
+        None => {
+            let info = account_info(key, System::id(), vec![0; len]);
+            *info.lamports.borrow_mut() = leak(0);
+            info
+        }
+    };
+    info.is_writable = true;
+    info
+}
+
+/// A gatekeeper config bound to a fresh mint and controller program
+pub struct Gatekeeper {
+    pub config: Config,
+}
+
+impl Gatekeeper {
+    pub fn new() -> Self {
+        let authority = Pubkey::new_unique();
+        let controller_program = Pubkey::new_unique();
+        Self {
+            config: Config {
+                authority,
+                compliance_authority: authority,
+                controller_program,
+                mint: Pubkey::new_unique(),
+                review_escrow: review_escrow(&controller_program),
+                ..Config::default()
+            },
+        }
+    }
+
+    pub fn mint(&self) -> Pubkey {
+        self.config.mint
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        self.config.authority
+    }
+
+    pub fn pda(&self, prefix: &[u8], owner: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[prefix, self.config.mint.as_ref(), owner.as_ref()], &ID).0
+    }
+
+    pub fn config_key(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"config", self.config.mint.as_ref()], &ID).0
+    }
+
+    pub fn config_account(&self) -> AccountInfo<'static> {
+        pda_account(self.config_key(), Some(&self.config), Config::LEN)
+    }
+
+    pub fn mint_account(&self) -> AccountInfo<'static> {
+        mint_account(self.config.mint)
+    }
+
+    pub fn controller_pda(&self) -> Pubkey {
+        Pubkey::find_program_address(
+            &[CONTROLLER_AUTHORITY_SEED],
+            &self.config.controller_program,
+        )
+        .0
+    }
+
+    /// The controller program's config holding the pause flag
+    pub fn pause_config(&self) -> Pubkey {
+        controller_config(&self.config.controller_program)
+    }
+
+    /// The controller program's PDA, signing as it does through `invoke_signed`
+    pub fn controller(&self) -> AccountInfo<'static> {
+        signer_info(self.controller_pda())
+    }
+}
@@ -0,0 +1,53 @@
+//! The controller program can only be replaced with its own PDA's consent.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{install_stubs, signer_info, Gatekeeper};
+use transfer_hook_gatekeeper::{instruction, review_escrow, Config, ID};
+
+fn set_controller_program(
+    gatekeeper: &Gatekeeper,
+    controller: AccountInfo<'static>,
+    controller_program: Pubkey,
+) -> std::result::Result<Config, ProgramError> {
+    install_stubs();
+    let config = gatekeeper.config_account();
+    let accounts = vec![
+        config.clone(),
+        signer_info(gatekeeper.authority()),
+        controller,
+        gatekeeper.mint_account(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::SetControllerProgram { controller_program }.data(),
+    )?;
+    let data = config.try_borrow_data()?;
+    Ok(Config::try_deserialize(&mut &data[..]).unwrap())
+}
+
+#[test]
+fn current_controller_hands_over() {
+    let gatekeeper = Gatekeeper::new();
+    let successor = Pubkey::new_unique();
+    let config = set_controller_program(&gatekeeper, gatekeeper.controller(), successor).unwrap();
+    assert_eq!(config.controller_program, successor);
+    assert_eq!(config.review_escrow, review_escrow(&successor));
+}
+
+#[test]
+fn authority_alone_cannot_replace_the_controller() {
+    let gatekeeper = Gatekeeper::new();
+    let attacker_program = Pubkey::new_unique();
+    let attacker_pda = Pubkey::find_program_address(
+        &[transfer_hook_gatekeeper::CONTROLLER_AUTHORITY_SEED],
+        &attacker_program,
+    )
+    .0;
+    assert_eq!(
+        set_controller_program(&gatekeeper, signer_info(attacker_pda), attacker_program).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintSeeds.into()))
+    );
+}
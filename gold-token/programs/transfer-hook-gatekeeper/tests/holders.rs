@@ -0,0 +1,83 @@
+//! Mints and burns skip the hook, so the controller program reports wallets it fills
+//! from empty or empties to keep the holder count in step.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{install_stubs, signer_info, Gatekeeper};
+use transfer_hook_gatekeeper::{instruction, Config, Rule, ID};
+
+fn gatekeeper() -> Gatekeeper {
+    let mut gatekeeper = Gatekeeper::new();
+    gatekeeper.config.policies = vec![Rule::HolderLimit];
+    gatekeeper.config.holder_count = 5;
+    gatekeeper
+}
+
+/// Run `data` signed by `controller`, returning the holder count afterwards
+fn update(
+    gatekeeper: &Gatekeeper,
+    controller: AccountInfo<'static>,
+    data: Vec<u8>,
+) -> std::result::Result<u64, ProgramError> {
+    install_stubs();
+    let config = gatekeeper.config_account();
+    let accounts = vec![config.clone(), controller, gatekeeper.mint_account()];
+    transfer_hook_gatekeeper::entry(&ID, Box::leak(accounts.into_boxed_slice()), &data)?;
+    let config = Config::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+    Ok(config.holder_count)
+}
+
+#[test]
+fn mint_into_an_empty_wallet_adds_a_holder() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        update(
+            &gatekeeper,
+            gatekeeper.controller(),
+            instruction::AddHolder {}.data()
+        ),
+        Ok(6)
+    );
+}
+
+#[test]
+fn burn_to_zero_removes_a_holder() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        update(
+            &gatekeeper,
+            gatekeeper.controller(),
+            instruction::RemoveHolder {}.data()
+        ),
+        Ok(4)
+    );
+}
+
+#[test]
+fn untracked_chain_leaves_the_count_alone() {
+    let mut gatekeeper = gatekeeper();
+    gatekeeper.config.policies = vec![Rule::Lockup];
+    assert_eq!(
+        update(
+            &gatekeeper,
+            gatekeeper.controller(),
+            instruction::AddHolder {}.data()
+        ),
+        Ok(5)
+    );
+}
+
+#[test]
+fn holder_count_changes_require_the_controller_pda() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        update(
+            &gatekeeper,
+            signer_info(Pubkey::new_unique()),
+            instruction::AddHolder {}.data()
+        )
+        .err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintSeeds.into()))
+    );
+}
@@ -0,0 +1,165 @@
+//! Only the mint's transfer-hook authority may bind a gatekeeper config to it.
+
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode, prelude::*, solana_program::program_option::COption, InstructionData,
+};
+use anchor_spl::token_2022::{
+    spl_token_2022::{
+        extension::{
+            transfer_hook::TransferHook, BaseStateWithExtensionsMut, ExtensionType,
+            StateWithExtensionsMut,
+        },
+        state::Mint,
+    },
+    ID as TOKEN_2022_PROGRAM_ID,
+};
+use common::{account_info, install_stubs, leak, signer_info, system_program};
+use spl_transfer_hook_interface::get_extra_account_metas_address;
+use transfer_hook_gatekeeper::{controller_config, instruction, Config, GatekeeperError, ID};
+
+struct Setup {
+    mint: Pubkey,
+    hook_authority: Pubkey,
+    hook_program: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            mint: Pubkey::new_unique(),
+            hook_authority: Pubkey::new_unique(),
+            hook_program: ID,
+        }
+    }
+
+    fn config_key(&self) -> Pubkey {
+        Pubkey::find_program_address(&[b"config", self.mint.as_ref()], &ID).0
+    }
+
+    fn mint_account(&self) -> AccountInfo<'static> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook])
+            .unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let hook = state.init_extension::<TransferHook>(true).unwrap();
+        hook.authority.0 = self.hook_authority;
+        hook.program_id.0 = self.hook_program;
+        state.base = Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: 0,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        account_info(self.mint, TOKEN_2022_PROGRAM_ID, data)
+    }
+
+    fn initialize(
+        &self,
+        authority: Pubkey,
+    ) -> std::result::Result<AccountInfo<'static>, ProgramError> {
+        install_stubs();
+        let mut config = account_info(self.config_key(), System::id(), vec![0; Config::LEN]);
+        *config.lamports.borrow_mut() = leak(0);
+        config.is_writable = true;
+        let accounts = vec![
+            signer_info(Pubkey::new_unique()),
+            signer_info(authority),
+            config.clone(),
+            self.mint_account(),
+            system_program(),
+        ];
+        transfer_hook_gatekeeper::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::Initialize {
+                controller_program: Pubkey::new_unique(),
+            }
+            .data(),
+        )
+        .map(|()| config)
+    }
+
+    fn initialize_extra_account_meta_list(
+        &self,
+        authority: Pubkey,
+    ) -> std::result::Result<(), ProgramError> {
+        let config = Config {
+            authority: self.hook_authority,
+            compliance_authority: self.hook_authority,
+            controller_program: Pubkey::new_unique(),
+            mint: self.mint,
+            ..Config::default()
+        };
+        let mut data = vec![];
+        config.try_serialize(&mut data).unwrap();
+        let mut meta_list = account_info(
+            get_extra_account_metas_address(&self.mint, &ID),
+            System::id(),
+            vec![],
+        );
+        meta_list.is_writable = true;
+        let accounts = vec![
+            signer_info(Pubkey::new_unique()),
+            account_info(self.config_key(), ID, data),
+            signer_info(authority),
+            meta_list,
+            self.mint_account(),
+            account_info(
+                controller_config(&config.controller_program),
+                config.controller_program,
+                vec![],
+            ),
+            system_program(),
+        ];
+        transfer_hook_gatekeeper::entry(
+            &ID,
+            Box::leak(accounts.into_boxed_slice()),
+            &instruction::InitializeExtraAccountMetaList {}.data(),
+        )
+    }
+}
+
+fn gatekeeper_error(error: GatekeeperError) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+#[test]
+fn hook_authority_can_initialize() {
+    let setup = Setup::new();
+    let config = setup.initialize(setup.hook_authority).unwrap();
+    let config = Config::try_deserialize(&mut &config.try_borrow_data().unwrap()[..]).unwrap();
+    assert_eq!(config.authority, setup.hook_authority);
+}
+
+#[test]
+fn front_runner_cannot_claim_config() {
+    let setup = Setup::new();
+    assert_eq!(
+        setup.initialize(Pubkey::new_unique()).unwrap_err(),
+        gatekeeper_error(GatekeeperError::Unauthorized)
+    );
+}
+
+#[test]
+fn mint_hooked_to_another_program_is_rejected() {
+    let mut setup = Setup::new();
+    setup.hook_program = Pubkey::new_unique();
+    assert_eq!(
+        setup.initialize(setup.hook_authority).unwrap_err(),
+        gatekeeper_error(GatekeeperError::HookNotConfigured)
+    );
+}
+
+#[test]
+fn extra_account_meta_list_requires_config_authority() {
+    let setup = Setup::new();
+    assert_eq!(
+        setup.initialize_extra_account_meta_list(Pubkey::new_unique()),
+        Err(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
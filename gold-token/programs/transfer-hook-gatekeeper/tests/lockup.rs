@@ -0,0 +1,153 @@
+//! Minting locks tokens for the configured period, each mint as its own tranche.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{account_info, install_stubs, signer_info, system_program, Gatekeeper, NOW};
+use transfer_hook_gatekeeper::{instruction, LockTranche, Lockup, ID, MAX_LOCK_TRANCHES};
+
+const LOCKUP_PERIOD: i64 = 2 * 24 * 60 * 60;
+
+fn gatekeeper() -> Gatekeeper {
+    let mut gatekeeper = Gatekeeper::new();
+    gatekeeper.config.lockup_period = LOCKUP_PERIOD;
+    gatekeeper
+}
+
+/// A lockup holding `tranches` of (amount, unlock_at)
+fn lockup(tranches: &[(u64, i64)]) -> Vec<u8> {
+    let mut lockup = Lockup::default();
+    for (slot, &(amount, unlock_at)) in tranches.iter().enumerate() {
+        lockup.tranches[slot] = LockTranche { amount, unlock_at };
+        lockup.locked_amount += amount;
+        lockup.unlock_at = lockup.unlock_at.max(unlock_at);
+    }
+    let mut data = vec![];
+    lockup.try_serialize(&mut data).unwrap();
+    data
+}
+
+/// Lock `amount` for a fresh wallet whose lockup account holds `existing`, returning the
+/// lockup account afterwards
+fn lock(
+    gatekeeper: &Gatekeeper,
+    controller: AccountInfo<'static>,
+    existing: Option<Vec<u8>>,
+    amount: u64,
+) -> std::result::Result<AccountInfo<'static>, ProgramError> {
+    install_stubs();
+    let wallet = Pubkey::new_unique();
+    let key = gatekeeper.pda(b"lockup", &wallet);
+    let mut lockup = match existing {
+        Some(data) => account_info(key, ID, data),
+        None => {
+            let info = account_info(key, System::id(), vec![]);
+            *info.lamports.borrow_mut() = common::leak(0);
+            info
+        }
+    };
+    lockup.is_writable = true;
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(Pubkey::new_unique()),
+        controller,
+        account_info(wallet, System::id(), vec![]),
+        lockup.clone(),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::LockTokens { amount }.data(),
+    )?;
+    Ok(lockup)
+}
+
+fn load(lockup: &AccountInfo) -> Lockup {
+    Lockup::try_deserialize(&mut &lockup.try_borrow_data().unwrap()[..]).unwrap()
+}
+
+#[test]
+fn first_mint_locks_for_the_configured_period() {
+    let gatekeeper = gatekeeper();
+    let lockup = load(&lock(&gatekeeper, gatekeeper.controller(), None, 500).unwrap());
+    assert_eq!(lockup.locked_amount, 500);
+    assert_eq!(lockup.unlock_at, NOW + LOCKUP_PERIOD);
+    assert_eq!(lockup.locked_at(NOW + LOCKUP_PERIOD - 1), 500);
+    assert_eq!(lockup.locked_at(NOW + LOCKUP_PERIOD), 0);
+}
+
+#[test]
+fn mint_during_a_running_lock_keeps_the_earlier_unlock() {
+    let gatekeeper = gatekeeper();
+    let running = lockup(&[(500, NOW + 60)]);
+    let lockup = load(&lock(&gatekeeper, gatekeeper.controller(), Some(running), 300).unwrap());
+    assert_eq!(lockup.locked_amount, 800);
+    assert_eq!(lockup.unlock_at, NOW + LOCKUP_PERIOD);
+    assert_eq!(lockup.locked_at(NOW + 59), 800);
+    assert_eq!(lockup.locked_at(NOW + 60), 300);
+}
+
+#[test]
+fn mint_after_expiry_drops_the_expired_tranche() {
+    let gatekeeper = gatekeeper();
+    let expired = lockup(&[(500, NOW)]);
+    let lockup = load(&lock(&gatekeeper, gatekeeper.controller(), Some(expired), 300).unwrap());
+    assert_eq!(lockup.locked_amount, 300);
+    assert_eq!(
+        lockup.tranches[..2],
+        [
+            LockTranche {
+                amount: 300,
+                unlock_at: NOW + LOCKUP_PERIOD
+            },
+            LockTranche::default()
+        ]
+    );
+}
+
+#[test]
+fn full_lockup_folds_the_earliest_tranche_into_the_next() {
+    let gatekeeper = gatekeeper();
+    let tranches: Vec<(u64, i64)> = (1..=MAX_LOCK_TRANCHES as i64)
+        .map(|hour| (100, NOW + hour * 3_600))
+        .collect();
+    let lockup = load(
+        &lock(
+            &gatekeeper,
+            gatekeeper.controller(),
+            Some(lockup(&tranches)),
+            300,
+        )
+        .unwrap(),
+    );
+    assert_eq!(lockup.locked_amount, 100 * MAX_LOCK_TRANCHES as u64 + 300);
+    // the first hour's tokens now unlock with the second hour's, never earlier
+    assert_eq!(
+        lockup.tranches[0],
+        LockTranche {
+            amount: 200,
+            unlock_at: NOW + 2 * 3_600
+        }
+    );
+    assert_eq!(lockup.locked_at(NOW + 3_600), lockup.locked_amount);
+}
+
+#[test]
+fn zero_lockup_period_locks_nothing() {
+    let mut gatekeeper = gatekeeper();
+    gatekeeper.config.lockup_period = 0;
+    let lockup = lock(&gatekeeper, gatekeeper.controller(), None, 500).unwrap();
+    assert_eq!(lockup.data_len(), 0);
+    assert_eq!(lockup.owner, &System::id());
+}
+
+#[test]
+fn lock_requires_the_controller_pda() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        lock(&gatekeeper, signer_info(Pubkey::new_unique()), None, 500).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintSeeds.into()))
+    );
+}
@@ -0,0 +1,223 @@
+//! The authority chooses which policies run, and the meta list follows the chain
+//! or is rebuilt from it on demand.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{account_info, install_stubs, signer_info, system_program, Gatekeeper};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address, instruction::ExecuteInstruction,
+};
+use transfer_hook_gatekeeper::{
+    extra_account_metas, instruction, Config, GatekeeperError, Rule, ID,
+};
+
+fn gatekeeper() -> Gatekeeper {
+    let mut gatekeeper = Gatekeeper::new();
+    gatekeeper.config.policies = vec![Rule::Allowlist, Rule::Velocity, Rule::Lockup];
+    gatekeeper
+}
+
+fn meta_list_account(gatekeeper: &Gatekeeper, pause_config: &Pubkey) -> AccountInfo<'static> {
+    let data = meta_list_data(pause_config, &gatekeeper.config.policies);
+    let key = get_extra_account_metas_address(&gatekeeper.mint(), &ID);
+    let mut info = account_info(key, ID, data);
+    info.is_writable = true;
+    info
+}
+
+/// Replace the chain, returning the stored config and meta list
+fn set_policies(
+    gatekeeper: &Gatekeeper,
+    pause_config: &Pubkey,
+    authority: Pubkey,
+    policies: Vec<Rule>,
+) -> std::result::Result<(Config, AccountInfo<'static>), ProgramError> {
+    install_stubs();
+    let config = gatekeeper.config_account();
+    let meta_list = meta_list_account(gatekeeper, pause_config);
+    let accounts = vec![
+        config.clone(),
+        signer_info(authority),
+        meta_list.clone(),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::SetPolicies { policies }.data(),
+    )?;
+    let config = Config::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+    Ok((config, meta_list))
+}
+
+/// Rebuild the list from the stored chain against `pause_config`, owned by `pause_owner`
+fn update_meta_list(
+    gatekeeper: &Gatekeeper,
+    current_pause_config: &Pubkey,
+    authority: Pubkey,
+    pause_config: Pubkey,
+    pause_owner: Pubkey,
+) -> std::result::Result<AccountInfo<'static>, ProgramError> {
+    install_stubs();
+    let meta_list = meta_list_account(gatekeeper, current_pause_config);
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(authority),
+        meta_list.clone(),
+        gatekeeper.mint_account(),
+        account_info(pause_config, pause_owner, vec![]),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::UpdateExtraAccountMetaList {}.data(),
+    )?;
+    Ok(meta_list)
+}
+
+/// Bytes `ExtraAccountMetaList::init` writes for `metas`
+fn meta_list_data(pause_config: &Pubkey, policies: &[Rule]) -> Vec<u8> {
+    let metas = extra_account_metas(pause_config, policies).unwrap();
+    let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+    data
+}
+
+fn chain_error(error: GatekeeperError) -> ProgramError {
+    ProgramError::Custom(error.into())
+}
+
+#[test]
+fn meta_list_is_regenerated_for_the_new_chain() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    let policies = vec![Rule::Lockup, Rule::MaxBalance];
+    let (config, meta_list) = set_policies(
+        &gatekeeper,
+        &pause_config,
+        gatekeeper.authority(),
+        policies.clone(),
+    )
+    .unwrap();
+    assert_eq!(config.policies, policies);
+
+    let expected = meta_list_data(&pause_config, &policies);
+    assert_eq!(
+        meta_list.try_borrow_data().unwrap()[..expected.len()],
+        expected[..]
+    );
+}
+
+#[test]
+fn chain_cannot_repeat_a_policy() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    assert_eq!(
+        set_policies(
+            &gatekeeper,
+            &pause_config,
+            gatekeeper.authority(),
+            vec![Rule::Lockup, Rule::Lockup]
+        )
+        .err(),
+        Some(chain_error(GatekeeperError::InvalidPolicyChain))
+    );
+}
+
+#[test]
+fn only_the_authority_sets_policies() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    assert_eq!(
+        set_policies(&gatekeeper, &pause_config, Pubkey::new_unique(), vec![]).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn meta_list_can_be_rebuilt_in_place() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    let meta_list = update_meta_list(
+        &gatekeeper,
+        &Pubkey::new_unique(),
+        gatekeeper.authority(),
+        pause_config,
+        gatekeeper.config.controller_program,
+    )
+    .unwrap();
+    assert_eq!(
+        meta_list.try_borrow_data().unwrap()[..],
+        meta_list_data(&pause_config, &gatekeeper.config.policies)[..]
+    );
+}
+
+#[test]
+fn only_the_authority_rebuilds_the_meta_list() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    assert_eq!(
+        update_meta_list(
+            &gatekeeper,
+            &pause_config,
+            Pubkey::new_unique(),
+            pause_config,
+            gatekeeper.config.controller_program,
+        )
+        .err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn rebuilt_meta_list_only_pins_the_controller_config() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    assert_eq!(
+        update_meta_list(
+            &gatekeeper,
+            &pause_config,
+            gatekeeper.authority(),
+            pause_config,
+            Pubkey::new_unique(),
+        )
+        .err(),
+        Some(chain_error(GatekeeperError::InvalidPauseConfig))
+    );
+}
+
+#[test]
+fn rebuilt_meta_list_rejects_other_controller_accounts() {
+    let gatekeeper = gatekeeper();
+    let pause_config = gatekeeper.pause_config();
+    assert_eq!(
+        update_meta_list(
+            &gatekeeper,
+            &pause_config,
+            gatekeeper.authority(),
+            Pubkey::new_unique(),
+            gatekeeper.config.controller_program,
+        )
+        .err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintSeeds.into()))
+    );
+}
+
+#[test]
+fn chain_cannot_keep_a_foreign_pause_config() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        set_policies(
+            &gatekeeper,
+            &Pubkey::new_unique(),
+            gatekeeper.authority(),
+            vec![Rule::Lockup]
+        )
+        .err(),
+        Some(chain_error(GatekeeperError::InvalidPauseConfig))
+    );
+}
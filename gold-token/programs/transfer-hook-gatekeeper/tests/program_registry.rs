@@ -0,0 +1,109 @@
+//! The authority curates which programs' accounts may hold the mint.
+
+mod common;
+
+use anchor_lang::{prelude::*, InstructionData};
+use common::{install_stubs, pda_account, signer_info, system_program, Gatekeeper};
+use transfer_hook_gatekeeper::{
+    instruction, GatekeeperError, ProgramRegistry, ID, MAX_APPROVED_PROGRAMS,
+};
+
+fn registry_account(
+    gatekeeper: &Gatekeeper,
+    existing: Option<ProgramRegistry>,
+) -> AccountInfo<'static> {
+    let key =
+        Pubkey::find_program_address(&[b"program_registry", gatekeeper.mint().as_ref()], &ID).0;
+    pda_account(key, existing.as_ref(), ProgramRegistry::LEN)
+}
+
+fn approve(
+    gatekeeper: &Gatekeeper,
+    existing: Option<ProgramRegistry>,
+    program_id: Pubkey,
+) -> std::result::Result<ProgramRegistry, ProgramError> {
+    install_stubs();
+    let registry = registry_account(gatekeeper, existing);
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        registry.clone(),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::ApproveProgram { program_id }.data(),
+    )?;
+    let data = registry.try_borrow_data()?;
+    Ok(ProgramRegistry::try_deserialize(&mut &data[..]).unwrap())
+}
+
+fn revoke(
+    gatekeeper: &Gatekeeper,
+    existing: ProgramRegistry,
+    program_id: Pubkey,
+) -> std::result::Result<ProgramRegistry, ProgramError> {
+    install_stubs();
+    let registry = registry_account(gatekeeper, Some(existing));
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(gatekeeper.authority()),
+        registry.clone(),
+        gatekeeper.mint_account(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::RevokeProgram { program_id }.data(),
+    )?;
+    let data = registry.try_borrow_data()?;
+    Ok(ProgramRegistry::try_deserialize(&mut &data[..]).unwrap())
+}
+
+#[test]
+fn first_approval_creates_the_registry() {
+    let gatekeeper = Gatekeeper::new();
+    let program_id = Pubkey::new_unique();
+    let registry = approve(&gatekeeper, None, program_id).unwrap();
+    assert_eq!(registry.programs, vec![program_id]);
+}
+
+#[test]
+fn approving_twice_keeps_one_entry() {
+    let gatekeeper = Gatekeeper::new();
+    let program_id = Pubkey::new_unique();
+    let existing = ProgramRegistry {
+        programs: vec![program_id],
+    };
+    let registry = approve(&gatekeeper, Some(existing), program_id).unwrap();
+    assert_eq!(registry.programs, vec![program_id]);
+}
+
+#[test]
+fn full_registry_rejects_new_programs() {
+    let gatekeeper = Gatekeeper::new();
+    let existing = ProgramRegistry {
+        programs: (0..MAX_APPROVED_PROGRAMS)
+            .map(|_| Pubkey::new_unique())
+            .collect(),
+    };
+    assert_eq!(
+        approve(&gatekeeper, Some(existing), Pubkey::new_unique()).err(),
+        Some(ProgramError::Custom(
+            GatekeeperError::ProgramRegistryFull.into()
+        ))
+    );
+}
+
+#[test]
+fn revoked_program_leaves_the_registry() {
+    let gatekeeper = Gatekeeper::new();
+    let (kept, revoked) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let existing = ProgramRegistry {
+        programs: vec![kept, revoked],
+    };
+    let registry = revoke(&gatekeeper, existing, revoked).unwrap();
+    assert_eq!(registry.programs, vec![kept]);
+}
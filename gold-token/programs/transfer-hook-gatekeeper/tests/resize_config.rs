@@ -0,0 +1,136 @@
+//! Configs created by the original program, which held only the authority, grow in place.
+
+mod common;
+
+use anchor_lang::{prelude::*, Discriminator, InstructionData};
+use common::{account_info, install_stubs, signer_info, system_program, Gatekeeper};
+use transfer_hook_gatekeeper::{
+    instruction, review_escrow, Config, GatekeeperError, RuleMode, DEFAULT_POLICIES, ID,
+    MAX_POLICIES,
+};
+
+/// Resize a config holding `data` for `gatekeeper`'s controller program, returning the
+/// stored config
+fn resize(
+    gatekeeper: &Gatekeeper,
+    authority: Pubkey,
+    data: Vec<u8>,
+) -> std::result::Result<Config, ProgramError> {
+    resize_with(
+        gatekeeper,
+        authority,
+        data,
+        gatekeeper.config.controller_program,
+    )
+}
+
+fn resize_with(
+    gatekeeper: &Gatekeeper,
+    authority: Pubkey,
+    data: Vec<u8>,
+    controller_program: Pubkey,
+) -> std::result::Result<Config, ProgramError> {
+    install_stubs();
+    let mut config = account_info(gatekeeper.config_key(), ID, data);
+    config.is_writable = true;
+    let accounts = vec![
+        config.clone(),
+        signer_info(authority),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::ResizeConfig { controller_program }.data(),
+    )?;
+    assert_eq!(config.data_len(), Config::LEN);
+    let data = config.try_borrow_data()?;
+    Ok(Config::try_deserialize(&mut &data[..]).unwrap())
+}
+
+/// Config as the original program stored it
+fn baseline(authority: &Pubkey) -> Vec<u8> {
+    let mut data = Config::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(authority.as_ref());
+    assert_eq!(data.len(), Config::BASELINE_LEN);
+    data
+}
+
+#[test]
+fn baseline_config_grows_to_the_current_layout() {
+    let gatekeeper = Gatekeeper::new();
+    let authority = gatekeeper.authority();
+    let config = resize(&gatekeeper, authority, baseline(&authority)).unwrap();
+    assert_eq!(config.authority, authority);
+    assert_eq!(config.compliance_authority, authority);
+    assert_eq!(config.mint, gatekeeper.mint());
+    assert_eq!(config.policies, DEFAULT_POLICIES);
+    assert!(config.rule_modes == [RuleMode::Enforce; MAX_POLICIES]);
+    assert_eq!(
+        config.controller_program,
+        gatekeeper.config.controller_program
+    );
+    assert_eq!(
+        config.review_escrow,
+        review_escrow(&gatekeeper.config.controller_program)
+    );
+}
+
+#[test]
+fn baseline_config_needs_a_controller_program() {
+    let gatekeeper = Gatekeeper::new();
+    let authority = gatekeeper.authority();
+    assert_eq!(
+        resize_with(
+            &gatekeeper,
+            authority,
+            baseline(&authority),
+            Pubkey::default()
+        )
+        .err(),
+        Some(ProgramError::Custom(
+            GatekeeperError::InvalidControllerProgram.into()
+        ))
+    );
+}
+
+#[test]
+fn current_config_is_left_as_is() {
+    let gatekeeper = Gatekeeper::new();
+    let mut data = vec![];
+    gatekeeper.config.try_serialize(&mut data).unwrap();
+    data.resize(Config::LEN, 0);
+    let config = resize(&gatekeeper, gatekeeper.authority(), data).unwrap();
+    assert_eq!(
+        config.controller_program,
+        gatekeeper.config.controller_program
+    );
+    assert_eq!(config.policies, gatekeeper.config.policies);
+}
+
+#[test]
+fn config_of_an_unknown_layout_is_rejected() {
+    let gatekeeper = Gatekeeper::new();
+    let authority = gatekeeper.authority();
+    let mut data = baseline(&authority);
+    data.resize(Config::LEN - 1, 0);
+    assert_eq!(
+        resize(&gatekeeper, authority, data).err(),
+        Some(ProgramError::Custom(GatekeeperError::InvalidConfig.into()))
+    );
+}
+
+#[test]
+fn only_the_authority_resizes() {
+    let gatekeeper = Gatekeeper::new();
+    assert_eq!(
+        resize(
+            &gatekeeper,
+            Pubkey::new_unique(),
+            baseline(&gatekeeper.authority())
+        )
+        .err(),
+        Some(ProgramError::Custom(GatekeeperError::Unauthorized.into()))
+    );
+}
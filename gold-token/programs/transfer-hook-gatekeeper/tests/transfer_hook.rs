@@ -0,0 +1,1082 @@
+//! Resolves the gatekeeper's ExtraAccountMetaList the same way Token-2022 does
+//! during a transfer and runs the resulting `Execute` instruction through the
+//! program entrypoint.
+
+mod common;
+
+use anchor_lang::{
+    error::ErrorCode,
+    prelude::*,
+    solana_program::{instruction::Instruction, program_option::COption},
+    Discriminator,
+};
+use anchor_spl::token_2022::{
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, transfer_hook::TransferHookAccount,
+            BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+        },
+        state::{Account as TokenAccount, AccountState, Mint},
+    },
+    ID as TOKEN_2022_PROGRAM_ID,
+};
+use common::{account_info, install_stubs, mint_account, NOW};
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{execute, ExecuteInstruction},
+};
+use transfer_hook_gatekeeper::{
+    extra_account_metas, review_escrow, AllowlistEntry, BlacklistEntry, Config, GatekeeperError,
+    KycAttestation, LockTranche, Lockup, ProgramRegistry, Rule, RuleMode, TravelRuleEntry,
+    TravelRuleRecord, VelocityExemption, VelocityState, CONTROLLER_PAUSED_OFFSET, ID, MAX_POLICIES,
+    VELOCITY_WINDOW,
+};
+
+fn entry_pda(prefix: &[u8], mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[prefix, mint.as_ref(), owner.as_ref()], &ID).0
+}
+
+fn blacklist_pda(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    entry_pda(b"blacklist", mint, owner)
+}
+
+fn allowlist_pda(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    entry_pda(b"allowlist", mint, owner)
+}
+
+fn velocity_exemption_pda(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    entry_pda(b"velocity_exempt", mint, owner)
+}
+
+struct Transfer {
+    mint: Pubkey,
+    source_owner: Pubkey,
+    destination_owner: Pubkey,
+    source_token: Pubkey,
+    destination_token: Pubkey,
+    /// Delegate signing in place of the source owner
+    delegate: Option<Pubkey>,
+    pause_config: Pubkey,
+    paused: bool,
+    /// Whether Token-2022 flagged the source account as mid-transfer
+    transferring: bool,
+    config: Config,
+    /// Transfer fee the mint charges, in basis points
+    transfer_fee_basis_points: Option<u16>,
+    source_balance: u64,
+    destination_balance: u64,
+    destination_kyc: Option<KycAttestation>,
+    source_kyc: Option<KycAttestation>,
+    source_velocity: Option<VelocityState>,
+    source_lockup: Option<Lockup>,
+    travel_rule_record: Option<TravelRuleRecord>,
+    /// Program owning the destination owner account, the system program for a wallet
+    destination_owner_program: Pubkey,
+    approved_programs: Option<ProgramRegistry>,
+    /// Blacklist, allowlist and exemption PDAs that exist on chain
+    entries: Vec<Pubkey>,
+}
+
+impl Transfer {
+    fn new() -> Self {
+        let mint = Pubkey::new_unique();
+        let controller_program = Pubkey::new_unique();
+        Self {
+            mint,
+            source_owner: Pubkey::new_unique(),
+            destination_owner: Pubkey::new_unique(),
+            source_token: Pubkey::new_unique(),
+            destination_token: Pubkey::new_unique(),
+            delegate: None,
+            pause_config: Pubkey::new_unique(),
+            paused: false,
+            config: Config {
+                authority: Pubkey::new_unique(),
+                compliance_authority: Pubkey::new_unique(),
+                controller_program,
+                review_escrow: review_escrow(&controller_program),
+                mint,
+                policies: vec![
+                    Rule::Velocity,
+                    Rule::Lockup,
+                    Rule::MaxBalance,
+                    Rule::HolderLimit,
+                ],
+                ..Config::default()
+            },
+            transferring: true,
+            transfer_fee_basis_points: None,
+            source_balance: 1_000_000_000,
+            destination_balance: 1_000_000_000,
+            destination_kyc: None,
+            source_kyc: None,
+            source_velocity: None,
+            source_lockup: None,
+            travel_rule_record: None,
+            destination_owner_program: System::id(),
+            approved_programs: None,
+            entries: vec![],
+        }
+    }
+
+    fn blacklist(&mut self, mint: Pubkey, owner: Pubkey) {
+        self.entries.push(blacklist_pda(&mint, &owner));
+    }
+
+    fn allowlist(&mut self, owner: Pubkey) {
+        self.entries.push(allowlist_pda(&self.mint, &owner));
+    }
+
+    fn exempt_from_velocity(&mut self, owner: Pubkey) {
+        self.entries
+            .push(velocity_exemption_pda(&self.mint, &owner));
+    }
+
+    fn token_account(
+        &self,
+        key: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+        transferring: bool,
+    ) -> AccountInfo<'static> {
+        let len = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+            ExtensionType::TransferHookAccount,
+        ])
+        .unwrap();
+        let mut data = vec![0; len];
+        let mut state =
+            StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+        state
+            .init_extension::<TransferHookAccount>(true)
+            .unwrap()
+            .transferring = transferring.into();
+        state.base = TokenAccount {
+            mint: self.mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        account_info(key, TOKEN_2022_PROGRAM_ID, data)
+    }
+
+    fn mint_account(&self) -> AccountInfo<'static> {
+        let Some(basis_points) = self.transfer_fee_basis_points else {
+            return mint_account(self.mint);
+        };
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let fee_config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        for fee in [
+            &mut fee_config.older_transfer_fee,
+            &mut fee_config.newer_transfer_fee,
+        ] {
+            fee.transfer_fee_basis_points = basis_points.into();
+            fee.maximum_fee = u64::MAX.into();
+        }
+        state.base = Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: 1_000_000_000_000,
+            decimals: 9,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        account_info(self.mint, TOKEN_2022_PROGRAM_ID, data)
+    }
+
+    fn extra_account_meta_list(&self) -> AccountInfo<'static> {
+        let metas = extra_account_metas(&self.pause_config, &self.config.policies).unwrap();
+        let mut data = vec![0; ExtraAccountMetaList::size_of(metas.len()).unwrap()];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &metas).unwrap();
+        account_info(get_extra_account_metas_address(&self.mint, &ID), ID, data)
+    }
+
+    fn config_account(&self) -> AccountInfo<'static> {
+        let mut data = vec![];
+        self.config.try_serialize(&mut data).unwrap();
+        let key = Pubkey::find_program_address(&[b"config", self.mint.as_ref()], &ID).0;
+        let mut info = account_info(key, ID, data);
+        info.is_writable = true;
+        info
+    }
+
+    fn kyc_account(
+        &self,
+        owner: Pubkey,
+        attestation: &Option<KycAttestation>,
+    ) -> AccountInfo<'static> {
+        let key = entry_pda(b"kyc", &self.mint, &owner);
+        match attestation {
+            Some(attestation) => {
+                let mut data = vec![];
+                attestation.try_serialize(&mut data).unwrap();
+                account_info(key, ID, data)
+            }
+            None => account_info(key, System::id(), vec![]),
+        }
+    }
+
+    fn lockup_account(&self) -> AccountInfo<'static> {
+        let key = entry_pda(b"lockup", &self.mint, &self.source_owner);
+        match &self.source_lockup {
+            Some(lockup) => {
+                let mut data = vec![];
+                lockup.try_serialize(&mut data).unwrap();
+                account_info(key, ID, data)
+            }
+            None => account_info(key, System::id(), vec![]),
+        }
+    }
+
+    fn velocity_account(&self) -> AccountInfo<'static> {
+        let key = entry_pda(b"velocity", &self.mint, &self.source_owner);
+        let mut info = match &self.source_velocity {
+            Some(state) => {
+                let mut data = vec![];
+                state.try_serialize(&mut data).unwrap();
+                account_info(key, ID, data)
+            }
+            None => account_info(key, System::id(), vec![]),
+        };
+        info.is_writable = true;
+        info
+    }
+
+    fn travel_rule_account(&self) -> AccountInfo<'static> {
+        let key = Pubkey::find_program_address(
+            &[
+                b"travel_rule",
+                self.mint.as_ref(),
+                self.source_owner.as_ref(),
+                self.destination_owner.as_ref(),
+            ],
+            &ID,
+        )
+        .0;
+        let mut info = match &self.travel_rule_record {
+            Some(record) => {
+                let mut data = vec![];
+                record.try_serialize(&mut data).unwrap();
+                account_info(key, ID, data)
+            }
+            None => account_info(key, System::id(), vec![]),
+        };
+        info.is_writable = true;
+        info
+    }
+
+    /// Every account the resolver may ask for beyond the five fixed ones
+    fn account_pool(&self) -> Vec<AccountInfo<'static>> {
+        let mut pool = vec![];
+        let owners = [self.source_owner, self.destination_owner];
+        for owner in owners.into_iter().chain(self.delegate) {
+            for (key, discriminator) in [
+                (
+                    blacklist_pda(&self.mint, &owner),
+                    BlacklistEntry::DISCRIMINATOR,
+                ),
+                (
+                    allowlist_pda(&self.mint, &owner),
+                    AllowlistEntry::DISCRIMINATOR,
+                ),
+                (
+                    velocity_exemption_pda(&self.mint, &owner),
+                    VelocityExemption::DISCRIMINATOR,
+                ),
+            ] {
+                if self.entries.contains(&key) {
+                    pool.push(account_info(key, ID, discriminator.to_vec()));
+                } else {
+                    pool.push(account_info(key, System::id(), vec![]));
+                }
+            }
+        }
+        let mut pause_config = vec![0; CONTROLLER_PAUSED_OFFSET + 1];
+        pause_config[CONTROLLER_PAUSED_OFFSET] = self.paused as u8;
+        pool.push(account_info(
+            self.pause_config,
+            Pubkey::new_unique(),
+            pause_config,
+        ));
+        pool.push(self.config_account());
+        pool.push(self.kyc_account(self.destination_owner, &self.destination_kyc));
+        pool.push(self.kyc_account(self.source_owner, &self.source_kyc));
+        pool.push(self.velocity_account());
+        pool.push(self.lockup_account());
+        pool.push(self.travel_rule_account());
+        pool.push(account_info(
+            self.destination_owner,
+            self.destination_owner_program,
+            vec![1],
+        ));
+        let registry =
+            Pubkey::find_program_address(&[b"program_registry", self.mint.as_ref()], &ID).0;
+        pool.push(match &self.approved_programs {
+            Some(registry_state) => {
+                let mut data = vec![];
+                registry_state.try_serialize(&mut data).unwrap();
+                account_info(registry, ID, data)
+            }
+            None => account_info(registry, System::id(), vec![]),
+        });
+        pool
+    }
+
+    /// Build the hook instruction exactly as Token-2022 would for `amount`
+    fn resolve(&self, amount: u64) -> (Instruction, Vec<AccountInfo<'static>>) {
+        let authority = self.delegate.unwrap_or(self.source_owner);
+        let meta_list = self.extra_account_meta_list();
+        let mut instruction = execute(
+            &ID,
+            &self.source_token,
+            &self.mint,
+            &self.destination_token,
+            &authority,
+            amount,
+        );
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(*meta_list.key, false));
+        let mut account_infos = vec![
+            self.token_account(
+                self.source_token,
+                self.source_owner,
+                self.source_balance,
+                self.transferring,
+            ),
+            self.mint_account(),
+            self.token_account(
+                self.destination_token,
+                self.destination_owner,
+                self.destination_balance,
+                false,
+            ),
+            account_info(authority, System::id(), vec![]),
+            meta_list.clone(),
+        ];
+        ExtraAccountMetaList::add_to_cpi_instruction::<ExecuteInstruction>(
+            &mut instruction,
+            &mut account_infos,
+            &meta_list.try_borrow_data().unwrap(),
+            &self.account_pool(),
+        )
+        .unwrap();
+        (instruction, account_infos)
+    }
+
+    fn execute(&self, amount: u64) -> std::result::Result<(), ProgramError> {
+        install_stubs();
+        let (instruction, account_infos) = self.resolve(amount);
+        transfer_hook_gatekeeper::entry(
+            &ID,
+            Box::leak(account_infos.into_boxed_slice()),
+            &instruction.data,
+        )
+    }
+}
+
+fn blacklisted() -> ProgramError {
+    ProgramError::Custom(GatekeeperError::AddressBlacklisted.into())
+}
+
+#[test]
+fn transfer_between_clean_wallets_is_approved() {
+    assert_eq!(Transfer::new().execute(100), Ok(()));
+}
+
+#[test]
+fn destination_entry_is_derived_from_token_account_owner() {
+    let transfer = Transfer::new();
+    let (instruction, _) = transfer.resolve(100);
+    assert_eq!(
+        instruction.accounts[5].pubkey,
+        blacklist_pda(&transfer.mint, &transfer.source_owner)
+    );
+    assert_eq!(
+        instruction.accounts[6].pubkey,
+        blacklist_pda(&transfer.mint, &transfer.destination_owner)
+    );
+}
+
+#[test]
+fn blacklisted_sender_cannot_transfer_out() {
+    let mut transfer = Transfer::new();
+    transfer.blacklist(transfer.mint, transfer.source_owner);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+#[test]
+fn blacklisted_recipient_cannot_receive() {
+    let mut transfer = Transfer::new();
+    transfer.blacklist(transfer.mint, transfer.destination_owner);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+#[test]
+fn delegate_can_move_tokens_of_a_clean_owner() {
+    let mut transfer = Transfer::new();
+    transfer.delegate = Some(Pubkey::new_unique());
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn blacklisted_delegate_cannot_move_tokens() {
+    let mut transfer = Transfer::new();
+    let delegate = Pubkey::new_unique();
+    transfer.delegate = Some(delegate);
+    transfer.blacklist(transfer.mint, delegate);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+#[test]
+fn delegate_cannot_move_tokens_of_a_blacklisted_owner() {
+    let mut transfer = Transfer::new();
+    transfer.delegate = Some(Pubkey::new_unique());
+    transfer.blacklist(transfer.mint, transfer.source_owner);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+#[test]
+fn standalone_hook_call_is_rejected() {
+    let mut transfer = Transfer::new();
+    transfer.transferring = false;
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::NotTransferring.into()
+        ))
+    );
+}
+
+#[test]
+fn config_bound_to_another_mint_is_rejected() {
+    let mut transfer = Transfer::new();
+    transfer.config.mint = Pubkey::new_unique();
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn blacklist_on_another_mint_does_not_apply() {
+    let mut transfer = Transfer::new();
+    let other_mint = Pubkey::new_unique();
+    transfer.blacklist(other_mint, transfer.source_owner);
+    transfer.blacklist(other_mint, transfer.destination_owner);
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn transfers_are_rejected_while_paused() {
+    let mut transfer = Transfer::new();
+    transfer.paused = true;
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::TransfersPaused.into()
+        ))
+    );
+}
+
+#[test]
+fn allowlist_mode_requires_both_parties_to_be_verified() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Allowlist);
+    let not_allowlisted = Err(ProgramError::Custom(
+        GatekeeperError::AddressNotAllowlisted.into(),
+    ));
+    assert_eq!(transfer.execute(100), not_allowlisted);
+
+    transfer.allowlist(transfer.source_owner);
+    assert_eq!(transfer.execute(100), not_allowlisted);
+
+    transfer.allowlist(transfer.destination_owner);
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn allowlist_mode_still_honours_the_blacklist() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Allowlist);
+    transfer.allowlist(transfer.source_owner);
+    transfer.allowlist(transfer.destination_owner);
+    transfer.blacklist(transfer.mint, transfer.destination_owner);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+fn kyc_attestation(tier: u8, expires_at: i64) -> Option<KycAttestation> {
+    Some(KycAttestation {
+        tier,
+        provider: Pubkey::new_unique(),
+        country_code: *b"CH",
+        issued_at: NOW - 86_400,
+        expires_at,
+    })
+}
+
+fn kyc_error(error: GatekeeperError) -> std::result::Result<(), ProgramError> {
+    Err(ProgramError::Custom(error.into()))
+}
+
+#[test]
+fn attestation_mode_requires_a_recipient_attestation() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Kyc);
+    assert_eq!(
+        transfer.execute(100),
+        kyc_error(GatekeeperError::KycMissing)
+    );
+
+    transfer.destination_kyc = kyc_attestation(0, NOW + 86_400);
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn expired_attestation_cannot_receive() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Kyc);
+    transfer.destination_kyc = kyc_attestation(2, NOW);
+    assert_eq!(
+        transfer.execute(100),
+        kyc_error(GatekeeperError::KycExpired)
+    );
+}
+
+#[test]
+fn under_tier_recipient_cannot_receive() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Kyc);
+    transfer.config.min_kyc_tier = 2;
+    transfer.destination_kyc = kyc_attestation(1, NOW + 86_400);
+    assert_eq!(
+        transfer.execute(100),
+        kyc_error(GatekeeperError::KycTierTooLow)
+    );
+}
+
+#[test]
+fn tier_balance_limit_applies_to_post_transfer_balance() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Kyc);
+    transfer.config.kyc_tier_max_balance = [0, 5_000, 0, 0];
+    transfer.destination_kyc = kyc_attestation(1, NOW + 86_400);
+
+    transfer.destination_balance = 5_000;
+    assert_eq!(transfer.execute(100), Ok(()));
+
+    transfer.destination_balance = 5_001;
+    assert_eq!(
+        transfer.execute(100),
+        kyc_error(GatekeeperError::KycBalanceLimitExceeded)
+    );
+}
+
+fn velocity_error(error: GatekeeperError) -> std::result::Result<(), ProgramError> {
+    Err(ProgramError::Custom(error.into()))
+}
+
+/// Velocity state after each `(sent_at, amount)` send
+fn velocity_state(sends: &[(i64, u64)]) -> Option<VelocityState> {
+    let mut state = VelocityState::default();
+    for &(sent_at, amount) in sends {
+        state.record(sent_at, amount);
+    }
+    Some(state)
+}
+
+#[test]
+fn velocity_limit_caps_outbound_within_the_window() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.source_velocity = velocity_state(&[(NOW - 3_600, 900)]);
+    assert_eq!(transfer.execute(100), Ok(()));
+    assert_eq!(
+        transfer.execute(101),
+        velocity_error(GatekeeperError::VelocityLimitExceeded)
+    );
+}
+
+#[test]
+fn outbound_leaves_the_window_a_day_later() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.source_velocity = velocity_state(&[(NOW - VELOCITY_WINDOW, 1_000)]);
+    assert_eq!(transfer.execute(1_000), Ok(()));
+}
+
+#[test]
+fn velocity_window_rolls_instead_of_resetting() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    // A fixed window opened a day ago would reset now and allow another 1_000
+    transfer.source_velocity = velocity_state(&[(NOW - VELOCITY_WINDOW, 100), (NOW - 60, 900)]);
+    assert_eq!(transfer.execute(100), Ok(()));
+    assert_eq!(
+        transfer.execute(101),
+        velocity_error(GatekeeperError::VelocityLimitExceeded)
+    );
+}
+
+#[test]
+fn limited_sender_needs_velocity_state() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    assert_eq!(
+        transfer.execute(100),
+        velocity_error(GatekeeperError::VelocityStateMissing)
+    );
+}
+
+#[test]
+fn exempt_wallet_bypasses_velocity_limit() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.exempt_from_velocity(transfer.source_owner);
+    assert_eq!(transfer.execute(1_000_000), Ok(()));
+}
+
+#[test]
+fn release_from_review_escrow_bypasses_velocity_limit() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.source_owner = transfer.config.review_escrow;
+    assert_eq!(transfer.execute(1_000_000), Ok(()));
+}
+
+#[test]
+fn deposit_into_review_escrow_bypasses_velocity_limit() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.source_velocity = velocity_state(&[(NOW, 1_000)]);
+    transfer.destination_owner = transfer.config.review_escrow;
+    assert_eq!(transfer.execute(1_000_000), Ok(()));
+}
+
+#[test]
+fn velocity_limit_follows_sender_kyc_tier() {
+    let mut transfer = Transfer::new();
+    transfer.config.velocity_limit = [1_000, 0, 5_000, 0];
+    transfer.source_kyc = kyc_attestation(2, NOW + 86_400);
+    transfer.source_velocity = velocity_state(&[]);
+    assert_eq!(transfer.execute(5_000), Ok(()));
+    assert_eq!(
+        transfer.execute(5_001),
+        velocity_error(GatekeeperError::VelocityLimitExceeded)
+    );
+
+    // An expired attestation falls back to the tier 0 limit
+    transfer.source_kyc = kyc_attestation(2, NOW);
+    assert_eq!(
+        transfer.execute(1_001),
+        velocity_error(GatekeeperError::VelocityLimitExceeded)
+    );
+}
+
+fn holding_error(error: GatekeeperError) -> std::result::Result<(), ProgramError> {
+    Err(ProgramError::Custom(error.into()))
+}
+
+#[test]
+fn max_balance_applies_to_post_transfer_balance() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_balance = 10_000;
+    transfer.destination_balance = 10_000;
+    assert_eq!(transfer.execute(100), Ok(()));
+
+    transfer.destination_balance = 10_001;
+    assert_eq!(
+        transfer.execute(100),
+        holding_error(GatekeeperError::MaxBalanceExceeded)
+    );
+}
+
+#[test]
+fn new_holder_beyond_cap_is_rejected() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_holders = 10;
+    transfer.config.holder_count = 10;
+
+    // Topping up an existing holder does not add one
+    transfer.destination_balance = 200;
+    assert_eq!(transfer.execute(100), Ok(()));
+
+    transfer.destination_balance = 100;
+    assert_eq!(
+        transfer.execute(100),
+        holding_error(GatekeeperError::HolderLimitExceeded)
+    );
+}
+
+#[test]
+fn new_holder_is_detected_net_of_the_transfer_fee() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_holders = 10;
+    transfer.config.holder_count = 10;
+    transfer.transfer_fee_basis_points = Some(100);
+
+    // 1% of 100 is withheld, so an empty destination ends up holding 99
+    transfer.destination_balance = 99;
+    assert_eq!(
+        transfer.execute(100),
+        holding_error(GatekeeperError::HolderLimitExceeded)
+    );
+
+    transfer.destination_balance = 100;
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn sender_leaving_makes_room_for_new_holder() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_holders = 10;
+    transfer.config.holder_count = 10;
+    transfer.source_balance = 0;
+    transfer.destination_balance = 100;
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+/// Lockup holding `tranches` of (amount, unlock_at)
+fn lockup(tranches: &[(u64, i64)]) -> Lockup {
+    let mut lockup = Lockup::default();
+    for (slot, &(amount, unlock_at)) in tranches.iter().enumerate() {
+        lockup.tranches[slot] = LockTranche { amount, unlock_at };
+    }
+    lockup
+}
+
+#[test]
+fn locked_tokens_cannot_leave_before_unlock() {
+    let mut transfer = Transfer::new();
+    transfer.source_lockup = Some(lockup(&[(600, NOW + 3_600), (400, NOW + 7_200)]));
+    transfer.source_balance = 1_000;
+    assert_eq!(transfer.execute(500), Ok(()));
+
+    transfer.source_balance = 999;
+    assert_eq!(
+        transfer.execute(500),
+        Err(ProgramError::Custom(GatekeeperError::TokensLocked.into()))
+    );
+}
+
+#[test]
+fn expired_tranches_are_released_while_later_ones_stay_locked() {
+    let mut transfer = Transfer::new();
+    transfer.source_lockup = Some(lockup(&[(600, NOW), (400, NOW + 3_600)]));
+    transfer.source_balance = 400;
+    assert_eq!(transfer.execute(600), Ok(()));
+
+    transfer.source_balance = 399;
+    assert_eq!(
+        transfer.execute(601),
+        Err(ProgramError::Custom(GatekeeperError::TokensLocked.into()))
+    );
+}
+
+#[test]
+fn expired_lockup_no_longer_restricts() {
+    let mut transfer = Transfer::new();
+    transfer.source_lockup = Some(lockup(&[(1_000, NOW)]));
+    transfer.source_balance = 0;
+    assert_eq!(transfer.execute(1_000), Ok(()));
+}
+
+#[test]
+fn monitored_rules_report_but_approve() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.insert(0, Rule::Kyc);
+    transfer.config.velocity_limit = [1_000, 0, 0, 0];
+    transfer.source_velocity = velocity_state(&[(NOW, 1_000)]);
+    transfer.config.rule_modes[Rule::Kyc as usize] = RuleMode::Monitor;
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::VelocityLimitExceeded.into()
+        ))
+    );
+
+    transfer.config.rule_modes[Rule::Velocity as usize] = RuleMode::Monitor;
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn rules_switched_off_are_skipped() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_balance = 10_000;
+    transfer.destination_balance = 10_001;
+    transfer.config.rule_modes[Rule::MaxBalance as usize] = RuleMode::Off;
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn monitor_mode_does_not_cover_the_blacklist() {
+    let mut transfer = Transfer::new();
+    transfer.config.rule_modes = [RuleMode::Monitor; MAX_POLICIES];
+    transfer.blacklist(transfer.mint, transfer.source_owner);
+    assert_eq!(transfer.execute(100), Err(blacklisted()));
+}
+
+#[test]
+fn meta_list_only_resolves_accounts_of_chained_policies() {
+    let mut transfer = Transfer::new();
+    transfer.config.policies = vec![];
+    let (instruction, _) = transfer.resolve(100);
+    assert_eq!(instruction.accounts.len(), 10);
+    assert!(!instruction.accounts[8].is_writable);
+
+    transfer.config.policies = vec![Rule::Lockup, Rule::HolderLimit];
+    let (instruction, _) = transfer.resolve(100);
+    assert_eq!(instruction.accounts.len(), 11);
+    assert!(instruction.accounts[8].is_writable);
+    assert_eq!(
+        instruction.accounts[10].pubkey,
+        entry_pda(b"lockup", &transfer.mint, &transfer.source_owner)
+    );
+}
+
+#[test]
+fn policies_outside_the_chain_are_skipped() {
+    let mut transfer = Transfer::new();
+    transfer.config.max_balance = 10_000;
+    transfer.destination_balance = 10_001;
+    transfer
+        .config
+        .policies
+        .retain(|&rule| rule != Rule::MaxBalance);
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn policy_accounts_must_follow_the_chain() {
+    let transfer = Transfer::new();
+    let invalid = Err(ProgramError::Custom(
+        GatekeeperError::InvalidPolicyAccount.into(),
+    ));
+    let run = |account_infos: Vec<AccountInfo<'static>>| {
+        install_stubs();
+        transfer_hook_gatekeeper::entry(
+            &ID,
+            Box::leak(account_infos.into_boxed_slice()),
+            &transfer.resolve(100).0.data,
+        )
+    };
+
+    // velocity state and exemption swapped
+    let (_, mut account_infos) = transfer.resolve(100);
+    account_infos.swap(10, 11);
+    assert_eq!(run(account_infos), invalid);
+
+    // lockup missing
+    let (_, mut account_infos) = transfer.resolve(100);
+    account_infos.pop();
+    assert_eq!(run(account_infos), invalid);
+}
+
+fn travel_rule_transfer() -> Transfer {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.push(Rule::TravelRule);
+    transfer.config.travel_rule_threshold = 1_000;
+    transfer
+}
+
+/// Record whose pending entries cover `amounts`, registered under nonces from 1
+fn travel_rule_record(amounts: &[u64]) -> Option<TravelRuleRecord> {
+    let mut record = TravelRuleRecord {
+        nonce: 1,
+        consumed: true,
+        ..TravelRuleRecord::default()
+    };
+    for (slot, &amount) in amounts.iter().enumerate() {
+        let entry = TravelRuleEntry {
+            nonce: slot as u64 + 1,
+            amount,
+            payload_hash: [7; 32],
+            registered_at: NOW,
+        };
+        record.pending[slot] = entry;
+        record.nonce = entry.nonce;
+        record.amount = entry.amount;
+        record.payload_hash = entry.payload_hash;
+        record.registered_at = entry.registered_at;
+        record.consumed = false;
+    }
+    Some(record)
+}
+
+impl Transfer {
+    /// Run a transfer of `amount`, returning the travel-rule record account afterwards
+    fn execute_travel_rule(&self, amount: u64) -> AccountInfo<'static> {
+        install_stubs();
+        let (instruction, account_infos) = self.resolve(amount);
+        let record = account_infos.last().unwrap().clone();
+        transfer_hook_gatekeeper::entry(
+            &ID,
+            Box::leak(account_infos.into_boxed_slice()),
+            &instruction.data,
+        )
+        .unwrap();
+        record
+    }
+}
+
+#[test]
+fn transfer_above_travel_rule_threshold_needs_a_record() {
+    let transfer = travel_rule_transfer();
+    assert_eq!(transfer.execute(1_000), Ok(()));
+    assert_eq!(
+        transfer.execute(1_001),
+        Err(ProgramError::Custom(
+            GatekeeperError::TravelRuleRecordMissing.into()
+        ))
+    );
+}
+
+#[test]
+fn travel_rule_record_must_match_and_be_unused() {
+    let missing = Err(ProgramError::Custom(
+        GatekeeperError::TravelRuleRecordMissing.into(),
+    ));
+    let mut transfer = travel_rule_transfer();
+    transfer.travel_rule_record = travel_rule_record(&[]);
+    assert_eq!(transfer.execute(2_000), missing);
+
+    transfer.travel_rule_record = travel_rule_record(&[2_500]);
+    assert_eq!(transfer.execute(2_000), missing);
+}
+
+#[test]
+fn matching_travel_rule_record_is_consumed() {
+    let mut transfer = travel_rule_transfer();
+    transfer.travel_rule_record = travel_rule_record(&[2_000]);
+    let record = transfer.execute_travel_rule(2_000);
+    let record =
+        TravelRuleRecord::try_deserialize(&mut &record.try_borrow_data().unwrap()[..]).unwrap();
+    assert!(record.consumed);
+    assert_eq!(record.pending[0], TravelRuleEntry::default());
+}
+
+#[test]
+fn each_pending_record_covers_its_own_transfer() {
+    let mut transfer = travel_rule_transfer();
+    transfer.travel_rule_record = travel_rule_record(&[2_000, 3_000, 2_000]);
+    let record = transfer.execute_travel_rule(2_000);
+    let record =
+        TravelRuleRecord::try_deserialize(&mut &record.try_borrow_data().unwrap()[..]).unwrap();
+    // the oldest matching entry is used, and the later ones stay pending in order
+    assert_eq!(record.pending.map(|entry| entry.nonce), [2, 3, 0, 0]);
+    assert!(!record.consumed);
+}
+
+/// A key on the ed25519 curve, as a wallet's is
+fn wallet() -> Pubkey {
+    std::iter::repeat_with(Pubkey::new_unique)
+        .find(Pubkey::is_on_curve)
+        .unwrap()
+}
+
+/// An off-curve key, as a PDA's is
+fn program_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"vault"], &Pubkey::new_unique()).0
+}
+
+fn program_allowlist_transfer() -> Transfer {
+    let mut transfer = Transfer::new();
+    transfer.config.policies.push(Rule::ProgramAllowlist);
+    transfer.destination_owner = wallet();
+    transfer
+}
+
+#[test]
+fn wallets_pass_the_program_allowlist() {
+    let transfer = program_allowlist_transfer();
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn unapproved_program_cannot_receive() {
+    let mut transfer = program_allowlist_transfer();
+    transfer.destination_owner_program = Pubkey::new_unique();
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![Pubkey::new_unique()],
+    });
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::DestinationProgramNotApproved.into()
+        ))
+    );
+}
+
+#[test]
+fn approved_program_vault_can_receive() {
+    let mut transfer = program_allowlist_transfer();
+    let pool_program = Pubkey::new_unique();
+    transfer.destination_owner_program = pool_program;
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![pool_program],
+    });
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn unfunded_program_address_cannot_receive_unless_approved() {
+    let mut transfer = program_allowlist_transfer();
+    transfer.destination_owner = program_address();
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![Pubkey::new_unique()],
+    });
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::DestinationProgramNotApproved.into()
+        ))
+    );
+
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![transfer.destination_owner],
+    });
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn review_escrow_passes_the_program_allowlist() {
+    let mut transfer = program_allowlist_transfer();
+    transfer.destination_owner = transfer.config.review_escrow;
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![Pubkey::new_unique()],
+    });
+    assert_eq!(transfer.execute(100), Ok(()));
+}
+
+#[test]
+fn unfunded_vault_of_an_approved_program_is_approved_on_its_own() {
+    let mut transfer = program_allowlist_transfer();
+    let pool_program = Pubkey::new_unique();
+    let vault = Pubkey::find_program_address(&[b"vault"], &pool_program).0;
+    transfer.destination_owner = vault;
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![pool_program],
+    });
+    assert_eq!(
+        transfer.execute(100),
+        Err(ProgramError::Custom(
+            GatekeeperError::DestinationProgramNotApproved.into()
+        ))
+    );
+
+    transfer.approved_programs = Some(ProgramRegistry {
+        programs: vec![pool_program, vault],
+    });
+    assert_eq!(transfer.execute(100), Ok(()));
+}
@@ -0,0 +1,297 @@
+//! Only the VASP role registers travel-rule records, nonces never repeat and unused
+//! records stay pending until their transfer or until the VASP cancels them.
+
+mod common;
+
+use anchor_lang::{error::ErrorCode, prelude::*, InstructionData};
+use common::{account_info, install_stubs, leak, signer_info, system_program, Gatekeeper, NOW};
+use transfer_hook_gatekeeper::{
+    instruction, GatekeeperError, TravelRuleEntry, TravelRuleRecord, ID,
+    MAX_PENDING_TRAVEL_RULE_RECORDS,
+};
+
+/// Record account of `source` and `destination` holding `existing`
+fn record_account(
+    gatekeeper: &Gatekeeper,
+    source: &Pubkey,
+    destination: &Pubkey,
+    existing: Option<Vec<u8>>,
+) -> AccountInfo<'static> {
+    let key = Pubkey::find_program_address(
+        &[
+            b"travel_rule",
+            gatekeeper.mint().as_ref(),
+            source.as_ref(),
+            destination.as_ref(),
+        ],
+        &ID,
+    )
+    .0;
+    let mut record = match existing {
+        Some(data) => account_info(key, ID, data),
+        None => {
+            let info = account_info(key, System::id(), vec![]);
+            *info.lamports.borrow_mut() = leak(0);
+            info
+        }
+    };
+    record.is_writable = true;
+    record
+}
+
+/// Register `nonce` for a transfer between two fresh wallets whose record account holds
+/// `existing`, returning the record account afterwards
+fn register(
+    gatekeeper: &Gatekeeper,
+    vasp_authority: Pubkey,
+    existing: Option<Vec<u8>>,
+    nonce: u64,
+) -> std::result::Result<AccountInfo<'static>, ProgramError> {
+    install_stubs();
+    let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let record = record_account(gatekeeper, &source, &destination, existing);
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(vasp_authority),
+        account_info(source, System::id(), vec![]),
+        account_info(destination, System::id(), vec![]),
+        record.clone(),
+        gatekeeper.mint_account(),
+        system_program(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::RegisterTravelRuleRecord {
+            nonce,
+            amount: 2_000,
+            payload_hash: [7; 32],
+        }
+        .data(),
+    )?;
+    Ok(record)
+}
+
+/// Cancel `nonce` in a record holding `existing`, returning the record account afterwards
+fn cancel(
+    gatekeeper: &Gatekeeper,
+    vasp_authority: Pubkey,
+    existing: Vec<u8>,
+    nonce: u64,
+) -> std::result::Result<AccountInfo<'static>, ProgramError> {
+    install_stubs();
+    let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let record = record_account(gatekeeper, &source, &destination, Some(existing));
+    let accounts = vec![
+        gatekeeper.config_account(),
+        signer_info(vasp_authority),
+        account_info(source, System::id(), vec![]),
+        account_info(destination, System::id(), vec![]),
+        record.clone(),
+        gatekeeper.mint_account(),
+    ];
+    transfer_hook_gatekeeper::entry(
+        &ID,
+        Box::leak(accounts.into_boxed_slice()),
+        &instruction::CancelTravelRuleRecord { nonce }.data(),
+    )?;
+    Ok(record)
+}
+
+fn load(record: &AccountInfo) -> TravelRuleRecord {
+    TravelRuleRecord::try_deserialize(&mut &record.try_borrow_data().unwrap()[..]).unwrap()
+}
+
+/// A record whose latest nonce is `nonce`, with `pending` nonces not yet used
+fn existing(nonce: u64, pending: &[u64]) -> Vec<u8> {
+    let mut record = TravelRuleRecord {
+        nonce,
+        consumed: !pending.contains(&nonce),
+        ..TravelRuleRecord::default()
+    };
+    for (slot, &nonce) in pending.iter().enumerate() {
+        record.pending[slot] = TravelRuleEntry {
+            nonce,
+            amount: 2_000,
+            ..TravelRuleEntry::default()
+        };
+    }
+    let mut data = vec![];
+    record.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn pending_nonces(record: &TravelRuleRecord) -> Vec<u64> {
+    record
+        .pending
+        .iter()
+        .map(|entry| entry.nonce)
+        .filter(|&nonce| nonce != 0)
+        .collect()
+}
+
+fn gatekeeper() -> Gatekeeper {
+    let mut gatekeeper = Gatekeeper::new();
+    gatekeeper.config.vasp_authority = Pubkey::new_unique();
+    gatekeeper
+}
+
+#[test]
+fn vasp_registers_a_pending_record() {
+    let gatekeeper = gatekeeper();
+    let record = load(&register(&gatekeeper, gatekeeper.config.vasp_authority, None, 1).unwrap());
+    assert_eq!(record.nonce, 1);
+    assert_eq!(record.amount, 2_000);
+    assert_eq!(record.registered_at, NOW);
+    assert!(!record.consumed);
+    assert_eq!(
+        record.pending[0],
+        TravelRuleEntry {
+            nonce: 1,
+            amount: 2_000,
+            payload_hash: [7; 32],
+            registered_at: NOW,
+        }
+    );
+}
+
+#[test]
+fn higher_nonce_leaves_an_unused_record_pending() {
+    let gatekeeper = gatekeeper();
+    let record = register(
+        &gatekeeper,
+        gatekeeper.config.vasp_authority,
+        Some(existing(5, &[5])),
+        6,
+    )
+    .unwrap();
+    assert_eq!(pending_nonces(&load(&record)), [5, 6]);
+}
+
+#[test]
+fn consumed_record_is_followed_by_a_higher_nonce() {
+    let gatekeeper = gatekeeper();
+    let record = register(
+        &gatekeeper,
+        gatekeeper.config.vasp_authority,
+        Some(existing(5, &[])),
+        6,
+    )
+    .unwrap();
+    let record = load(&record);
+    assert_eq!(record.nonce, 6);
+    assert!(!record.consumed);
+    assert_eq!(pending_nonces(&record), [6]);
+}
+
+#[test]
+fn nonce_cannot_be_reused() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        register(
+            &gatekeeper,
+            gatekeeper.config.vasp_authority,
+            Some(existing(5, &[])),
+            5
+        )
+        .err(),
+        Some(ProgramError::Custom(
+            GatekeeperError::InvalidTravelRuleNonce.into()
+        ))
+    );
+}
+
+#[test]
+fn pending_records_are_bounded() {
+    let gatekeeper = gatekeeper();
+    let pending: Vec<u64> = (1..=MAX_PENDING_TRAVEL_RULE_RECORDS as u64).collect();
+    let latest = *pending.last().unwrap();
+    assert_eq!(
+        register(
+            &gatekeeper,
+            gatekeeper.config.vasp_authority,
+            Some(existing(latest, &pending)),
+            latest + 1
+        )
+        .err(),
+        Some(ProgramError::Custom(
+            GatekeeperError::TravelRuleRecordsFull.into()
+        ))
+    );
+}
+
+#[test]
+fn only_the_vasp_registers_records() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        register(&gatekeeper, Pubkey::new_unique(), None, 1).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
+
+#[test]
+fn cancelling_frees_a_slot_in_a_full_record() {
+    let gatekeeper = gatekeeper();
+    let pending: Vec<u64> = (1..=MAX_PENDING_TRAVEL_RULE_RECORDS as u64).collect();
+    let latest = *pending.last().unwrap();
+    let record = cancel(
+        &gatekeeper,
+        gatekeeper.config.vasp_authority,
+        existing(latest, &pending),
+        2,
+    )
+    .unwrap();
+    let data = record.try_borrow_data().unwrap().to_vec();
+    let record = register(
+        &gatekeeper,
+        gatekeeper.config.vasp_authority,
+        Some(data),
+        latest + 1,
+    )
+    .unwrap();
+    let mut expected = pending.clone();
+    expected.retain(|&nonce| nonce != 2);
+    expected.push(latest + 1);
+    assert_eq!(pending_nonces(&load(&record)), expected);
+}
+
+#[test]
+fn cancelling_the_latest_record_leaves_it_unusable() {
+    let gatekeeper = gatekeeper();
+    let record = cancel(
+        &gatekeeper,
+        gatekeeper.config.vasp_authority,
+        existing(6, &[5, 6]),
+        6,
+    )
+    .unwrap();
+    let record = load(&record);
+    assert!(record.consumed);
+    assert_eq!(pending_nonces(&record), [5]);
+}
+
+#[test]
+fn only_pending_records_are_cancelled() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        cancel(
+            &gatekeeper,
+            gatekeeper.config.vasp_authority,
+            existing(6, &[6]),
+            5
+        )
+        .err(),
+        Some(ProgramError::Custom(
+            GatekeeperError::TravelRuleRecordNotPending.into()
+        ))
+    );
+}
+
+#[test]
+fn only_the_vasp_cancels_records() {
+    let gatekeeper = gatekeeper();
+    assert_eq!(
+        cancel(&gatekeeper, Pubkey::new_unique(), existing(6, &[6]), 6).err(),
+        Some(ProgramError::Custom(ErrorCode::ConstraintHasOne.into()))
+    );
+}
@@ -37,6 +37,12 @@ async function initialize() {
     gatekeeperProgram.programId
   );
 
+  // Initialization must be signed by the program's upgrade authority
+  const [programData] = PublicKey.findProgramAddressSync(
+    [goldTokenProgram.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   const [mintAuthority] = PublicKey.findProgramAddressSync(
     [Buffer.from("mint_authority")],
     goldTokenProgram.programId
@@ -59,6 +65,8 @@ async function initialize() {
         assetProtection: assetProtection.publicKey,
         feeController: feeController.publicKey,
         gatekeeperProgram: gatekeeperProgram.programId,
+        program: goldTokenProgram.programId,
+        programData: programData,
         config: config,
         gatekeeperConfig: gatekeeperConfig,
         extraAccountMetaList: extraAccountMetaList,
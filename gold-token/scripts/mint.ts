@@ -3,7 +3,6 @@ import { Program } from "@coral-xyz/anchor";
 import { GoldToken } from "../target/types/gold_token";
 import { PublicKey, Keypair } from "@solana/web3.js";
 import { TOKEN_2022_PROGRAM_ID, getAssociatedTokenAddress } from "@solana/spl-token";
-import { BN } from "@coral-xyz/anchor";
 
 async function mintTokens() {
   // Configure the client to use the local cluster
@@ -17,6 +16,7 @@ async function mintTokens() {
   const CONFIG_PDA = new PublicKey("3oN9az3bVpmQWPUgkzRGowYzeGnE9dZuxthmf3ShMt2u");
   const MINT_ADDRESS = new PublicKey("FAESU8Ks782mg4bd7eoMmXaY3ZVqaTDGpydoL1VDw24");
   const MINT_AUTHORITY_PDA = new PublicKey("FeYAcs3joh6YKp7tKc6BjqbZf3KNhfbUYGCWyMAhSHqA");
+  const GATEKEEPER_PROGRAM_ID = new PublicKey("CsMsG5FueDqKdmZ1THbBRhvN2NXkDVsaHCABDsfmL4Ld");
   
   // Supply Controller - Replace with your saved keypair
   const SUPPLY_CONTROLLER_SECRET = [115,166,50,109,115,25,36,12,55,90,139,191,63,185,252,61,108,183,151,85,246,178,16,115,165,113,240,131,50,209,94,49,227,136,132,63,161,234,91,42,148,132,76,42,33,209,25,118,116,92,246,246,90,85,86,89,12,15,141,94,202,173,222,194];
@@ -25,17 +25,28 @@ async function mintTokens() {
   // ============================================
   // MINT PARAMETERS
   // ============================================
-  const AMOUNT_TO_MINT = 1000; // 1000 tokens (with 9 decimals = 1000.000000000)
-  
-  // Create a recipient (or use existing wallet)
-  const recipient = Keypair.generate(); // Generate new user
-  // const recipient = new PublicKey("EXISTING_USER_ADDRESS"); // Or use existing address
+  // The bar the custodian registered a deposit for; the deposit fixes the amount and recipient
+  const REFINER = "REPLACE_WITH_REFINER";
+  const SERIAL = "REPLACE_WITH_SERIAL";
+
+  const [goldBar] = PublicKey.findProgramAddressSync(
+    [Buffer.from("gold_bar"), Buffer.from(REFINER), Buffer.from(SERIAL)],
+    goldTokenProgram.programId
+  );
+  const [barDeposit] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bar_deposit"), goldBar.toBuffer()],
+    goldTokenProgram.programId
+  );
+  const deposit = await goldTokenProgram.account.barDeposit.fetch(barDeposit);
+  const recipient = deposit.recipient;
+  const AMOUNT_TO_MINT = deposit.fineWeightMg.toNumber() / 1000; // 1 token = 1 gram of fine gold
 
   console.log("=== MINTING GOLD TOKENS ===");
   console.log("Config PDA:", CONFIG_PDA.toString());
   console.log("Mint Address:", MINT_ADDRESS.toString());
   console.log("Supply Controller:", supplyController.publicKey.toString());
-  console.log("Recipient:", recipient.publicKey.toString());
+  console.log("Bar Deposit:", barDeposit.toString(), `(${REFINER} ${SERIAL})`);
+  console.log("Recipient:", recipient.toString());
   console.log("Amount to mint:", AMOUNT_TO_MINT);
 
   // ============================================
@@ -43,29 +54,53 @@ async function mintTokens() {
   // ============================================
   const recipientTokenAccount = await getAssociatedTokenAddress(
     MINT_ADDRESS,
-    recipient.publicKey,
+    recipient,
     false, // allowOwnerOffCurve
     TOKEN_2022_PROGRAM_ID
   );
 
   console.log("Recipient Token Account:", recipientTokenAccount.toString());
 
+  // ============================================
+  // DERIVE GATEKEEPER LOCKUP ACCOUNTS
+  // ============================================
+  const [gatekeeperConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config"), MINT_ADDRESS.toBuffer()],
+    GATEKEEPER_PROGRAM_ID
+  );
+  const [lockup] = PublicKey.findProgramAddressSync(
+    [Buffer.from("lockup"), MINT_ADDRESS.toBuffer(), recipient.toBuffer()],
+    GATEKEEPER_PROGRAM_ID
+  );
+  const [gatekeeperController] = PublicKey.findProgramAddressSync(
+    [Buffer.from("gatekeeper_controller")],
+    goldTokenProgram.programId
+  );
+  const [reserveAttestation] = PublicKey.findProgramAddressSync(
+    [Buffer.from("reserve_attestation")],
+    goldTokenProgram.programId
+  );
+
   // ============================================
   // MINT TOKENS
   // ============================================
   try {
     const tx = await goldTokenProgram.methods
-      .mintTokens(
-        new BN(AMOUNT_TO_MINT * 10**9), // Convert to smallest unit (9 decimals)
-        recipient.publicKey
-      )
+      .mintAgainstDeposit()
       .accounts({
         config: CONFIG_PDA,
         supplyController: supplyController.publicKey,
         mint: MINT_ADDRESS,
         mintAuthorityPda: MINT_AUTHORITY_PDA,
-        recipient: recipient.publicKey,
+        barDeposit: barDeposit,
+        goldBar: goldBar,
+        recipient: recipient,
         recipientTokenAccount: recipientTokenAccount,
+        lockup: lockup,
+        gatekeeperProgram: GATEKEEPER_PROGRAM_ID,
+        gatekeeperController: gatekeeperController,
+        gatekeeperConfig: gatekeeperConfig,
+        reserveAttestation: reserveAttestation,
         tokenProgram: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: anchor.utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
@@ -77,15 +112,8 @@ async function mintTokens() {
     console.log("Transaction signature:", tx);
     console.log("\n=== RESULTS ===");
     console.log("✅ Minted", AMOUNT_TO_MINT, "GOLD tokens");
-    console.log("✅ To recipient:", recipient.publicKey.toString());
+    console.log("✅ To recipient:", recipient.toString());
     console.log("✅ Token account:", recipientTokenAccount.toString());
-
-    // ============================================
-    // SAVE RECIPIENT INFO
-    // ============================================
-    console.log("\n=== SAVE RECIPIENT INFO ===");
-    console.log("Recipient Public Key:", recipient.publicKey.toString());
-    console.log("Recipient Secret Key:", JSON.stringify(Array.from(recipient.secretKey)));
     
   } catch (error) {
     console.error("❌ Mint failed:", error);
@@ -104,19 +132,19 @@ async function mintTokens() {
 // HELPER FUNCTIONS
 // ============================================
 
-// Function to mint to multiple recipients
+// Function to mint against multiple bar deposits
 async function mintToMultipleUsers() {
   console.log("=== BATCH MINTING ===");
   
-  const recipients = [
-    { name: "Alice", amount: 1000 },
-    { name: "Bob", amount: 500 },
-    { name: "Charlie", amount: 2000 }
+  const bars = [
+    { refiner: "REFINER_A", serial: "SERIAL_1" },
+    { refiner: "REFINER_A", serial: "SERIAL_2" },
+    { refiner: "REFINER_B", serial: "SERIAL_3" }
   ];
 
-  for (const { name, amount } of recipients) {
-    console.log(`\nMinting ${amount} tokens to ${name}...`);
-    // Call mintTokens() with different parameters
+  for (const { refiner, serial } of bars) {
+    console.log(`\nMinting against the deposit for ${refiner} ${serial}...`);
+    // Call mintAgainstDeposit() for each bar's deposit
     // Implementation similar to above
   }
 }
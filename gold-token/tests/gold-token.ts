@@ -49,6 +49,12 @@ async function initializeGoldToken() {
     program.programId
   );
   
+  // Initialization must be signed by the program's upgrade authority
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
   const [mintAuthorityPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("mint_authority")],
     program.programId
@@ -114,6 +120,8 @@ async function initializeGoldToken() {
         assetProtection: assetProtection.publicKey,
         feeController: feeController.publicKey,
         gatekeeperProgram: gatekeeperProgramId,
+        program: program.programId,
+        programData: programData,
         config: configPda,
         gatekeeperConfig: gatekeeperConfig,
         extraAccountMetaList: extraAccountMetaList,